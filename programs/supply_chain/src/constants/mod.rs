@@ -1 +1,20 @@
-pub const ANCHOR_DISCRIMINATOR_SIZE:usize = 8;
\ No newline at end of file
+pub const ANCHOR_DISCRIMINATOR_SIZE:usize = 8;
+// Seller and purchase orders must be shipped within 7 days, otherwise the escrow can be refunded
+pub const ORDER_SHIPMENT_DEADLINE: u64 = 7 * 24 * 60 * 60;
+// Withdrawals below 1 SOL are rejected until the platform owner changes the minimum
+pub const DEFAULT_MIN_WITHDRAW_AMOUNT: u64 = 1_000_000_000;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+// No single fee can take more than 5% of a payment
+pub const MAX_FEE_BPS: u16 = 500;
+pub const DEFAULT_WITHDRAW_FEE_BPS: u16 = 200;
+// Number of price changes kept per product before the oldest is overwritten
+pub const PRICE_HISTORY_LEN: usize = 16;
+pub const MAX_INSPECTION_SCORE: u8 = 100;
+// Inspectors set the expiry of every inspection, at most one year after it was made
+pub const MAX_INSPECTION_VALIDITY_PERIOD: u64 = 365 * 24 * 60 * 60;
+// Inspectors must lock at least 0.5 SOL before they can accept or submit inspections
+pub const MIN_INSPECTOR_STAKE: u64 = 500_000_000;
+// A dispute must pay the reviewer at least 20% of the inspection fee it puts on hold
+pub const MIN_DISPUTE_REVIEW_FEE_BPS: u16 = 2_000;
+// Disputes not resolved within 14 days can be expired and the payout released to the inspector
+pub const DISPUTE_REVIEW_PERIOD: u64 = 14 * 24 * 60 * 60;
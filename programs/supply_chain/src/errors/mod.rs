@@ -38,7 +38,7 @@ pub enum SupplyChainErrors {
     ProductNotQualityChecked,
     #[msg("insufficient balance")]
     InsufficientBalance,
-    #[msg("should withdraw atleast 1 SOL")]
+    #[msg("withdraw amount is below the minimum")]
     InsifficentWithdraw,
     #[msg("insufficient stock")]
     InsufficientStock,
@@ -46,4 +46,98 @@ pub enum SupplyChainErrors {
     InvalidWarehouse,
     #[msg("logistics not found")]
     InvalidLogistics,
+    #[msg("role already assigned")]
+    RoleAlreadyAssigned,
+    #[msg("role not assigned")]
+    RoleNotAssigned,
+    #[msg("invalid order")]
+    InvalidOrder,
+    #[msg("order is not in a state that allows this action")]
+    InvalidOrderStatus,
+    #[msg("order shipment deadline has not passed yet")]
+    OrderDeadlineNotReached,
+    #[msg("escrow already settled")]
+    EscrowAlreadySettled,
+    #[msg("warehouse capacity exceeded")]
+    WarehouseCapacityExceeded,
+    #[msg("warehouse size can not be less than the used capacity")]
+    InvalidWarehouseSize,
+    #[msg("unit volume must be greater than zero")]
+    InvalidUnitVolume,
+    #[msg("shipment is not in transit")]
+    ShipmentNotInTransit,
+    #[msg("signer is not the current custodian")]
+    InvalidCustodian,
+    #[msg("location too long")]
+    InvalidLocation,
+    #[msg("condition too long")]
+    InvalidCondition,
+    #[msg("payment mint not configured")]
+    PaymentMintNotSet,
+    #[msg("invalid payment mint")]
+    InvalidPaymentMint,
+    #[msg("invalid token vault")]
+    InvalidTokenVault,
+    #[msg("seller not found")]
+    InvalidSeller,
+    #[msg("no ownership transfer pending")]
+    NoPendingOwner,
+    #[msg("program is paused")]
+    ProgramPaused,
+    #[msg("account is frozen")]
+    AccountFrozen,
+    #[msg("batch number too long")]
+    InvalidBatchNumber,
+    #[msg("invalid batch production or expiry date")]
+    InvalidBatchDates,
+    #[msg("batch has expired")]
+    BatchExpired,
+    #[msg("product image too long")]
+    InvalidProductImage,
+    #[msg("product has been recalled")]
+    ProductRecalled,
+    #[msg("product has not been recalled")]
+    ProductNotRecalled,
+    #[msg("nothing to refund")]
+    NothingToRefund,
+    #[msg("recall fund can not cover this refund")]
+    InsufficientRecallFund,
+    #[msg("inspection score out of range")]
+    InvalidInspectionScore,
+    #[msg("product has no passing inspection that is still valid")]
+    InspectionNotValid,
+    #[msg("inspection fee must be greater than zero")]
+    InvalidInspectionFee,
+    #[msg("invalid inspection request status")]
+    InvalidInspectionRequestStatus,
+    #[msg("inspection request does not match")]
+    InvalidInspectionRequest,
+    #[msg("inspector stake is below the minimum")]
+    InsufficientInspectorStake,
+    #[msg("inspector stake is still locked")]
+    StakeLocked,
+    #[msg("invalid inspection dispute status")]
+    InvalidInspectionDisputeStatus,
+    #[msg("dispute reason too long")]
+    InvalidDisputeReason,
+    #[msg("reviewer must be a different inspector")]
+    ReviewerNotIndependent,
+    #[msg("inspection expiry must be in the future and within the maximum validity period")]
+    InvalidInspectionValidity,
+    #[msg("invalid purchase order status")]
+    InvalidPurchaseOrderStatus,
+    #[msg("stock can only move between two different warehouses of the same owner")]
+    InvalidStockTransfer,
+    #[msg("stock transfer is not in transit")]
+    StockTransferNotInTransit,
+    #[msg("payment mint can only be set once")]
+    PaymentMintAlreadySet,
+    #[msg("fee exemption account does not belong to the payee")]
+    InvalidFeeExemption,
+    #[msg("batch was not released for sale")]
+    BatchNotReleased,
+    #[msg("review fee is below the minimum share of the inspection fee")]
+    ReviewFeeTooLow,
+    #[msg("dispute review period is not over yet")]
+    DisputeReviewPeriodNotOver,
 }
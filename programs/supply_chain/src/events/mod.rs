@@ -0,0 +1,479 @@
+use anchor_lang::prelude::*;
+
+use crate::states::{
+    balance_holder::BalanceHolderKind, inspection_dispute::InspectionDisputeStatus,
+    order::OrderStatus, purchase_order::PurchaseOrderStatus,
+};
+
+#[event]
+pub struct ProgramInitialized {
+    pub owner: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct OwnershipTransferProposed {
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct OwnershipTransferred {
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub recipient: Pubkey,
+    pub mint: Pubkey, // Pubkey::default() for lamports
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ProgramPauseChanged {
+    pub paused: bool,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct AccountFreezeChanged {
+    pub account: Pubkey,
+    pub kind: BalanceHolderKind,
+    pub frozen: bool,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct FeeScheduleChanged {
+    pub kind: BalanceHolderKind,
+    pub withdraw_fee_bps: u16,
+    pub purchase_fee_bps: u16,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct FeeExemptionChanged {
+    pub account: Pubkey,
+    pub exempt: bool,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PlatformFeeCollected {
+    pub payer: Pubkey,
+    pub payee: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct UserCreated {
+    pub user: Pubkey,
+    pub owner: Pubkey,
+    pub roles: u8,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct UserRolesChanged {
+    pub user: Pubkey,
+    pub roles: u8,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct FactoryCreated {
+    pub factory: Pubkey,
+    pub factory_id: u64,
+    pub owner: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ProductCreated {
+    pub product: Pubkey,
+    pub product_id: u64,
+    pub factory: Pubkey,
+    pub product_price: u64,
+    pub product_stock: u64,
+    pub mrp: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ProductUpdated {
+    pub product: Pubkey,
+    pub product_price: u64,
+    pub mrp: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ProductRecalled {
+    pub product: Pubkey,
+    pub recalled_by: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct RecallFunded {
+    pub product: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct RecallRefundClaimed {
+    pub product: Pubkey,
+    pub holder: Pubkey,
+    pub claimant: Pubkey,
+    pub quantity: u64,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct BatchCreated {
+    pub batch: Pubkey,
+    pub product: Pubkey,
+    pub batch_id: u64,
+    pub quantity: u64,
+    pub expires_at: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct BatchInspected {
+    pub batch: Pubkey,
+    pub product: Pubkey,
+    pub inspector: Pubkey,
+    pub quantity: u64,
+    pub passed: bool,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectorRegistered {
+    pub inspector: Pubkey,
+    pub owner: Pubkey,
+    pub fee_charge_per_product: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectorFeeChanged {
+    pub inspector: Pubkey,
+    pub old_fee: u64,
+    pub new_fee: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ProductInspected {
+    pub product: Pubkey,
+    pub inspection: Pubkey,
+    pub inspector: Pubkey,
+    pub inspection_id: u64,
+    pub passed: bool,
+    pub fee_charge_per_product: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectorStaked {
+    pub inspector: Pubkey,
+    pub amount: u64,
+    pub total: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectorUnstaked {
+    pub inspector: Pubkey,
+    pub amount: u64,
+    pub total: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectorSlashed {
+    pub inspector: Pubkey,
+    pub product: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectionRequested {
+    pub request: Pubkey,
+    pub product: Pubkey,
+    pub inspector: Pubkey,
+    pub requested_by: Pubkey,
+    pub fee: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectionRequestAccepted {
+    pub request: Pubkey,
+    pub inspector: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectionRequestCancelled {
+    pub request: Pubkey,
+    pub refunded: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectionDisputed {
+    pub dispute: Pubkey,
+    pub inspection: Pubkey,
+    pub raised_by: Pubkey,
+    pub frozen: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct DisputeReviewerAssigned {
+    pub dispute: Pubkey,
+    pub reviewer: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectionDisputeResolved {
+    pub dispute: Pubkey,
+    pub review_inspection: Pubkey,
+    pub upheld: bool,
+    pub amount: u64, // Released to the inspector when upheld, refunded to the factory otherwise
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectionDisputeClosed {
+    pub dispute: Pubkey,
+    pub status: InspectionDisputeStatus, // Cancelled by the raiser or expired without a review
+    pub released: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct InspectorPaid {
+    pub product: Pubkey,
+    pub inspector: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct WarehouseCreated {
+    pub warehouse: Pubkey,
+    pub warehouse_id: u64,
+    pub owner: Pubkey,
+    pub warehouse_size: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct WarehouseResized {
+    pub warehouse: Pubkey,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct WarehousePurchase {
+    pub warehouse: Pubkey,
+    pub product: Pubkey,
+    pub factory: Pubkey,
+    pub quantity: u64,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PurchaseOrderPlaced {
+    pub purchase_order: Pubkey,
+    pub warehouse: Pubkey,
+    pub factory: Pubkey,
+    pub product: Pubkey,
+    pub quantity: u64,
+    pub total_price: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PurchaseOrderStatusChanged {
+    pub purchase_order: Pubkey,
+    pub status: PurchaseOrderStatus,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PurchaseOrderRefunded {
+    pub purchase_order: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct WarehouseStockTransferred {
+    pub stock_transfer: Pubkey,
+    pub from_warehouse: Pubkey,
+    pub to_warehouse: Pubkey,
+    pub product: Pubkey,
+    pub quantity: u64,
+    pub shipment: Pubkey, // Default when moved without a carrier
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct WarehouseStockTransferReceived {
+    pub stock_transfer: Pubkey,
+    pub to_warehouse: Pubkey,
+    pub quantity: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct WarehouseStockTransferCancelled {
+    pub stock_transfer: Pubkey,
+    pub from_warehouse: Pubkey,
+    pub quantity: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct SellerCreated {
+    pub seller: Pubkey,
+    pub seller_id: u64,
+    pub owner: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct OrderCreated {
+    pub order: Pubkey,
+    pub seller: Pubkey,
+    pub warehouse: Pubkey,
+    pub product: Pubkey,
+    pub quantity: u64,
+    pub total_price: u64,
+    pub ship_by: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct OrderStatusChanged {
+    pub order: Pubkey,
+    pub status: OrderStatus,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct OrderRefunded {
+    pub order: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct LogisticsProviderCreated {
+    pub provider: Pubkey,
+    pub logistic_id: u64,
+    pub owner: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct ShipmentDispatched {
+    pub shipment: Pubkey,
+    pub order: Pubkey,
+    pub provider: Pubkey,
+    pub warehouse: Pubkey,
+    pub quantity: u64,
+    pub shipment_cost: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct CheckpointRecorded {
+    pub shipment: Pubkey,
+    pub checkpoint: Pubkey,
+    pub custodian: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct CustodyTransferred {
+    pub shipment: Pubkey,
+    pub checkpoint: Pubkey,
+    pub from_custodian: Pubkey,
+    pub to_custodian: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct OrderDelivered {
+    pub order: Pubkey,
+    pub shipment: Pubkey,
+    pub seller: Pubkey,
+    pub warehouse: Pubkey,
+    pub quantity: u64,
+    pub released_amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct CustomerPurchase {
+    pub customer_product: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub product: Pubkey,
+    pub quantity: u64,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct BalanceWithdrawn {
+    pub holder: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub platform_fee: u64,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct PaymentMintChanged {
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct TokenVaultCreated {
+    pub vault: Pubkey,
+    pub holder: Pubkey,
+    pub mint: Pubkey,
+    pub timestamp: u64,
+}
+
+#[event]
+pub struct MinWithdrawAmountChanged {
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub timestamp: u64,
+}
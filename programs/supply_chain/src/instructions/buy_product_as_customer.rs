@@ -1,11 +1,23 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
 use crate::{
     constants::ANCHOR_DISCRIMINATOR_SIZE,
     errors::SupplyChainErrors,
+    events::{CustomerPurchase, PlatformFeeCollected},
+    instructions::token_vault_instruction::transfer_tokens,
     states::{
-        customer_product::CustomerProduct, product::Product, seller::Seller,
-        seller_product_stock::SellerProductStock, transaction::Transaction, user::User,
+        balance_holder::BalanceHolderKind,
+        customer_product::CustomerProduct,
+        fee_exemption::FeeExemption,
+        product::Product,
+        program_state::ProgramState,
+        seller::Seller,
+        seller_product_stock::SellerProductStock,
+        transaction::Transaction,
+        treasury::Treasury,
+        user::{Role, User},
     },
 };
 
@@ -21,39 +33,113 @@ pub fn buy_product_as_customer_ctx(
     let product = &mut ctx.accounts.product;
 
     require!(
-        user.role == "CUSTOMER" || user.is_customer,
+        user.has_role(Role::Customer),
         SupplyChainErrors::UnauthorizedAccess
     );
+    require!(
+        seller_product.seller_pda == seller.key(),
+        SupplyChainErrors::InvalidSeller
+    );
     require!(
         stock <= seller_product.stock_quantity,
         SupplyChainErrors::InsufficientStock
     );
+    let now = Clock::get()?.unix_timestamp as u64;
+    require!(
+        product.inspection_valid_at(now),
+        SupplyChainErrors::InspectionNotValid
+    );
     let total_amount_to_pay = product
         .mrp
         .checked_mul(stock)
         .ok_or(SupplyChainErrors::Overflow)?;
-    let transaction_instruction = anchor_lang::solana_program::system_instruction::transfer(
-        &ctx.accounts.buyer.key(),
-        &seller.key(),
-        total_amount_to_pay,
-    );
-    let result = anchor_lang::solana_program::program::invoke(
-        &transaction_instruction,
-        &[
-            ctx.accounts.buyer.to_account_info(),
-            seller.to_account_info(),
-        ],
-    );
-    if let Err(e) = result {
-        msg!("Transaction to seller failed: {:?}", e);
-        return Err(e.into());
-    }
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        ctx.accounts
+            .program_state
+            .purchase_fee_for(BalanceHolderKind::Seller, total_amount_to_pay)?
+    };
+    let seller_amount = total_amount_to_pay - platform_fee;
+    // Lamports go to the seller account, tokens to its vault
+    let payment_destination = match ctx.accounts.payment_mint.as_ref() {
+        None => {
+            let transaction_instruction = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.buyer.key(),
+                &seller.key(),
+                seller_amount,
+            );
+            let result = anchor_lang::solana_program::program::invoke(
+                &transaction_instruction,
+                &[
+                    ctx.accounts.buyer.to_account_info(),
+                    seller.to_account_info(),
+                ],
+            );
+            if let Err(e) = result {
+                msg!("Transaction to seller failed: {:?}", e);
+                return Err(e.into());
+            }
+            if platform_fee > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.buyer.to_account_info(),
+                            to: ctx.accounts.treasury.to_account_info(),
+                        },
+                    ),
+                    platform_fee,
+                )?;
+            }
+            seller.balance = seller
+                .balance
+                .checked_add(seller_amount)
+                .ok_or(SupplyChainErrors::Overflow)?;
+            seller.key()
+        }
+        Some(payment_mint) => {
+            let (
+                Some(payer_token_account),
+                Some(seller_vault),
+                Some(treasury_vault),
+                Some(token_program),
+            ) = (
+                ctx.accounts.payer_token_account.as_ref(),
+                ctx.accounts.seller_vault.as_ref(),
+                ctx.accounts.treasury_vault.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            )
+            else {
+                return Err(SupplyChainErrors::InvalidTokenVault.into());
+            };
+            transfer_tokens(
+                payer_token_account,
+                seller_vault,
+                payment_mint,
+                &ctx.accounts.buyer,
+                token_program,
+                seller_amount,
+            )?;
+            if platform_fee > 0 {
+                transfer_tokens(
+                    payer_token_account,
+                    treasury_vault,
+                    payment_mint,
+                    &ctx.accounts.buyer,
+                    token_program,
+                    platform_fee,
+                )?;
+            }
+            seller_vault.key()
+        }
+    };
 
     transaction.transaction_id = user.transaction_count + 1;
     transaction.from = ctx.accounts.buyer.key();
-    transaction.to = seller.key();
+    transaction.to = payment_destination;
     transaction.amount = total_amount_to_pay;
-    transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+    transaction.timestamp = now;
     transaction.status = true;
 
     customer_product.product_id = product.product_id;
@@ -64,9 +150,9 @@ pub fn buy_product_as_customer_ctx(
     customer_product.product_pda = product.key();
     customer_product.owner = ctx.accounts.buyer.key();
     customer_product.seller_pda = seller.key();
-    customer_product.purchased_on = Clock::get()?.unix_timestamp as u64;
+    customer_product.purchased_on = now;
+    customer_product.unit_price = product.mrp;
 
-    seller.balance = total_amount_to_pay;
     seller_product.stock_quantity = seller_product
         .stock_quantity
         .checked_sub(stock)
@@ -75,9 +161,27 @@ pub fn buy_product_as_customer_ctx(
     user.transaction_count += 1;
     user.product_count += 1;
 
+    if platform_fee > 0 {
+        emit_cpi!(PlatformFeeCollected {
+            payer: ctx.accounts.buyer.key(),
+            payee: seller.key(),
+            amount: platform_fee,
+            timestamp: customer_product.purchased_on,
+        });
+    }
+    emit_cpi!(CustomerPurchase {
+        customer_product: customer_product.key(),
+        buyer: ctx.accounts.buyer.key(),
+        seller: seller.key(),
+        product: product.key(),
+        quantity: stock,
+        amount: total_amount_to_pay,
+        timestamp: customer_product.purchased_on,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BuyProductAsCustomerCtx<'info> {
     #[account(mut)]
@@ -89,7 +193,7 @@ pub struct BuyProductAsCustomerCtx<'info> {
         seeds = [b"customer_product",user.key().as_ref(),(user.product_count+1).to_le_bytes().as_ref()],
         bump,
     )]
-    pub customer_product: Account<'info, CustomerProduct>,
+    pub customer_product: Box<Account<'info, CustomerProduct>>,
     #[account(
         init,
         payer = buyer,
@@ -97,16 +201,52 @@ pub struct BuyProductAsCustomerCtx<'info> {
         seeds = [b"transaction",user.key().as_ref(),(user.transaction_count+1).to_le_bytes().as_ref()],
         bump,
     )]
-    pub transaction: Account<'info, Transaction>,
+    pub transaction: Box<Account<'info, Transaction>>,
     #[account(mut)]
-    pub seller_product: Account<'info, SellerProductStock>,
-    #[account(mut)]
-    pub seller: Account<'info, Seller>,
+    pub seller_product: Box<Account<'info, SellerProductStock>>,
+    #[account(mut, constraint = !seller.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub seller: Box<Account<'info, Seller>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", seller.owner.as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
     #[account(mut)]
-    pub user: Account<'info, User>,
+    pub user: Box<Account<'info, User>>,
     #[account(
         address = seller_product.product_pda,
+        constraint = !product.recalled @ SupplyChainErrors::ProductRecalled,
+    )]
+    pub product: Box<Account<'info, Product>>,
+    // Token accounts below are only required when paying with the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer,
+    )]
+    pub payer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"vault", seller.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+    )]
+    pub seller_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"vault", treasury.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
     )]
-    pub product: Account<'info, Product>,
+    pub treasury_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(address = program_state.payment_mint @ SupplyChainErrors::InvalidPaymentMint)]
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::events::OrderStatusChanged;
+use crate::states::{
+    order::{Order, OrderStatus},
+    seller::Seller,
+    user::{Role, User},
+    warehouse_stock::WarehouseStock,
+};
+
+pub fn cancel_order_instruction_as_seller(ctx: Context<CancelOrderAsSellerCtx>) -> Result<()> {
+    let user = &ctx.accounts.user;
+    let seller = &ctx.accounts.seller;
+    let order = &mut ctx.accounts.order;
+    let warehouse_stock = &mut ctx.accounts.warehouse_stock;
+
+    require!(
+        user.has_role(Role::Seller) && user.owner == ctx.accounts.seller_account.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        seller.owner == ctx.accounts.seller_account.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        order.seller_pda == seller.key(),
+        SupplyChainErrors::InvalidOrder
+    );
+    // Orders can only be cancelled before the warehouse ships them
+    order.transition_to(OrderStatus::Cancelled)?;
+    warehouse_stock.release(order.product_stock)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    warehouse_stock.updated_at = now;
+
+    emit_cpi!(OrderStatusChanged {
+        order: order.key(),
+        status: order.status,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelOrderAsSellerCtx<'info> {
+    pub seller_account: Signer<'info>,
+    #[account(mut)]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", order.warehouse_pda.as_ref(), order.product_pda.as_ref()],
+        bump,
+    )]
+    pub warehouse_stock: Account<'info, WarehouseStock>,
+    pub seller: Account<'info, Seller>,
+    pub user: Account<'info, User>,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SupplyChainErrors, events::MinWithdrawAmountChanged,
+    states::program_state::ProgramState,
+};
+
+pub fn update_min_withdraw_amount(
+    ctx: Context<UpdateMinWithdrawAmountCtx>,
+    amount: u64,
+) -> Result<()> {
+    let state = &mut ctx.accounts.program_state;
+    require!(
+        state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let old_amount = state.min_withdraw_amount;
+    state.min_withdraw_amount = amount;
+
+    emit_cpi!(MinWithdrawAmountChanged {
+        old_amount,
+        new_amount: amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateMinWithdrawAmountCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
@@ -1,18 +1,41 @@
 use anchor_lang::prelude::*;
 
-use crate::{errors::SupplyChainErrors, states::program_state::ProgramState};
+use crate::{
+    constants::MAX_FEE_BPS,
+    errors::SupplyChainErrors,
+    events::FeeScheduleChanged,
+    states::{balance_holder::BalanceHolderKind, program_state::ProgramState},
+};
 
-pub fn update_platform_fee(ctx: Context<UpdatePlatformFeeCtx>, fee: u64) -> Result<()> {
+pub fn update_platform_fee(
+    ctx: Context<UpdatePlatformFeeCtx>,
+    kind: BalanceHolderKind,
+    withdraw_fee_bps: u16,
+    purchase_fee_bps: u16,
+) -> Result<()> {
     let state = &mut ctx.accounts.program_state;
     require!(
         state.owner.key() == ctx.accounts.owner.key(),
         SupplyChainErrors::UnauthorizedAccess
     );
-    require!(fee <= 5, SupplyChainErrors::InvalidPlatformFee);
-    state.platform_fee = fee;
+    require!(
+        withdraw_fee_bps <= MAX_FEE_BPS && purchase_fee_bps <= MAX_FEE_BPS,
+        SupplyChainErrors::InvalidPlatformFee
+    );
+    let fees = state.fees_for_mut(kind);
+    fees.withdraw_fee_bps = withdraw_fee_bps;
+    fees.purchase_fee_bps = purchase_fee_bps;
+
+    emit_cpi!(FeeScheduleChanged {
+        kind,
+        withdraw_fee_bps,
+        purchase_fee_bps,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdatePlatformFeeCtx<'info> {
     #[account(mut)]
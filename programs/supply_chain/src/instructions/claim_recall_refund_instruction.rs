@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::SupplyChainErrors,
+    events::RecallRefundClaimed,
+    states::{
+        customer_product::CustomerProduct, product::Product, recall_fund::RecallFund,
+        seller::Seller, seller_product_stock::SellerProductStock,
+    },
+};
+
+// Customers are refunded the unit price they paid for every unit they still hold
+pub fn claim_recall_refund_as_customer(ctx: Context<ClaimRecallRefundAsCustomerCtx>) -> Result<()> {
+    let customer_product = &mut ctx.accounts.customer_product;
+    require!(
+        customer_product.owner == ctx.accounts.claimant.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let quantity = customer_product.stock_quantity;
+    let amount = customer_product
+        .unit_price
+        .checked_mul(quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    pay_refund(
+        &mut ctx.accounts.recall_fund,
+        &ctx.accounts.claimant.to_account_info(),
+        amount,
+    )?;
+    customer_product.stock_quantity = 0;
+
+    emit_cpi!(RecallRefundClaimed {
+        product: ctx.accounts.product.key(),
+        holder: customer_product.key(),
+        claimant: ctx.accounts.claimant.key(),
+        quantity,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+// Sellers are refunded the unit price they paid for every unit left in their stock
+pub fn claim_recall_refund_as_seller(ctx: Context<ClaimRecallRefundAsSellerCtx>) -> Result<()> {
+    let seller_product_stock = &mut ctx.accounts.seller_product_stock;
+    require!(
+        ctx.accounts.seller.owner == ctx.accounts.claimant.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let quantity = seller_product_stock.stock_quantity;
+    let amount = seller_product_stock
+        .unit_price
+        .checked_mul(quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    pay_refund(
+        &mut ctx.accounts.recall_fund,
+        &ctx.accounts.claimant.to_account_info(),
+        amount,
+    )?;
+    seller_product_stock.stock_quantity = 0;
+
+    emit_cpi!(RecallRefundClaimed {
+        product: ctx.accounts.product.key(),
+        holder: seller_product_stock.key(),
+        claimant: ctx.accounts.claimant.key(),
+        quantity,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+fn pay_refund<'info>(
+    recall_fund: &mut Account<'info, RecallFund>,
+    claimant: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, SupplyChainErrors::NothingToRefund);
+    let claimed = recall_fund
+        .claimed
+        .checked_add(amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    require!(
+        claimed <= recall_fund.funded,
+        SupplyChainErrors::InsufficientRecallFund
+    );
+    recall_fund.claimed = claimed;
+    **recall_fund.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **claimant.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRecallRefundAsCustomerCtx<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    #[account(mut)]
+    pub customer_product: Account<'info, CustomerProduct>,
+    #[account(
+        address = customer_product.product_pda,
+        constraint = product.recalled @ SupplyChainErrors::ProductNotRecalled,
+    )]
+    pub product: Account<'info, Product>,
+    #[account(mut, seeds = [b"recall_fund", product.key().as_ref()], bump)]
+    pub recall_fund: Account<'info, RecallFund>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRecallRefundAsSellerCtx<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    #[account(address = seller_product_stock.seller_pda)]
+    pub seller: Account<'info, Seller>,
+    #[account(mut)]
+    pub seller_product_stock: Account<'info, SellerProductStock>,
+    #[account(
+        address = seller_product_stock.product_pda,
+        constraint = product.recalled @ SupplyChainErrors::ProductNotRecalled,
+    )]
+    pub product: Account<'info, Product>,
+    #[account(mut, seeds = [b"recall_fund", product.key().as_ref()], bump)]
+    pub recall_fund: Account<'info, RecallFund>,
+}
@@ -1,11 +1,10 @@
 use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
 use crate::errors::SupplyChainErrors::*;
+use crate::events::WarehouseCreated;
 use crate::states::factory::Factory;
-use crate::states::product::Product;
-use crate::states::{user::User, warehouse::Warehouse};
+use crate::states::{user::{Role, User}, warehouse::Warehouse};
 use anchor_lang::prelude::*;
 use anchor_lang::{
-    account,
     prelude::Account,
     prelude::{Program, System},
 };
@@ -23,8 +22,7 @@ pub fn create_warehouse_instrution(
     let warehouse = &mut ctx.accounts.warehouse;
     let user = &mut ctx.accounts.user;
     let factory = &mut ctx.accounts.factory;
-    let product = &mut ctx.accounts.product;
-    if user.role != "WAREHOUSE" {
+    if !user.has_role(Role::Warehouse) {
         return Err(UnauthorizedAccess.into());
     }
     if factory.factory_id != factory_id {
@@ -43,13 +41,21 @@ pub fn create_warehouse_instrution(
     warehouse.created_at = Clock::get()?.unix_timestamp as u64;
     warehouse.product_count = 0;
     warehouse.warehouse_size = warehouse_size;
-    warehouse.product_pda = product.key();
-    warehouse.product_id = product.product_id;
+    warehouse.used_capacity = 0;
 
     user.warehouse_count += 1;
+
+    emit_cpi!(WarehouseCreated {
+        warehouse: warehouse.key(),
+        warehouse_id: warehouse.warehouse_id,
+        owner: warehouse.owner,
+        warehouse_size,
+        timestamp: warehouse.created_at,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateWarehouseCtx<'info> {
     #[account(
@@ -63,8 +69,6 @@ pub struct CreateWarehouseCtx<'info> {
     #[account(mut)]
     pub user: Account<'info, User>,
     #[account(mut)]
-    pub product: Account<'info, Product>,
-    #[account(mut)]
     pub factory: Account<'info, Factory>,
     #[account(mut)]
     pub owner: Signer<'info>,
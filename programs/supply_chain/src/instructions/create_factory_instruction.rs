@@ -3,7 +3,8 @@ use anchor_lang::prelude::*;
 use crate::{
     constants::ANCHOR_DISCRIMINATOR_SIZE,
     errors::SupplyChainErrors::*,
-    states::{factory::Factory, user::User},
+    events::FactoryCreated,
+    states::{factory::Factory, user::{Role, User}},
 };
 
 pub fn create_factory(
@@ -16,7 +17,7 @@ pub fn create_factory(
 ) -> Result<()> {
     let factory = &mut ctx.accounts.factory;
     let user = &mut ctx.accounts.user;
-    if user.role != "FACTORY" {
+    if !user.has_role(Role::Factory) {
         return Err(UnauthorizedAccess.into());
     }
     if name.len() > 32 {
@@ -39,9 +40,17 @@ pub fn create_factory(
     factory.balance = 0;
     factory.owner = ctx.accounts.owner.key();
     factory.created_at = Clock::get()?.unix_timestamp as u64;
+
+    emit_cpi!(FactoryCreated {
+        factory: factory.key(),
+        factory_id: factory.factory_id,
+        owner: factory.owner,
+        timestamp: factory.created_at,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateFactoryInstructionContext<'info> {
     #[account(mut)]
@@ -2,66 +2,62 @@ use anchor_lang::prelude::*;
 
 use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
 use crate::errors::SupplyChainErrors;
-use crate::states::product::Product;
-use crate::states::warehouse::Warehouse;
-use crate::states::{logistics::Logistics, user::User};
+use crate::events::LogisticsProviderCreated;
+use crate::states::{
+    logistics_provider::LogisticsProvider,
+    user::{Role, User},
+};
 
 pub fn create_logistics_instruction(
     ctx: Context<CreateLogisticsContext>,
     name: String,
     transportation_mode: String,
     contact_info: String,
-    product_id: u64,
-    warehouse_id: u64,
     latitude: f64,
     longitude: f64,
 ) -> Result<()> {
     let user = &mut ctx.accounts.user;
     let logistics = &mut ctx.accounts.logistics;
-    let warehouse = &mut ctx.accounts.warehouse;
-    let product = &mut ctx.accounts.product;
 
     require!(
         user.owner.key() == ctx.accounts.owner.key(),
         SupplyChainErrors::UnauthorizedAccess
     );
     require!(
-        user.role == "LOGISTICS",
+        user.has_role(Role::Logistics),
         SupplyChainErrors::UnauthorizedAccess
     );
     require!(name.len() < 32, SupplyChainErrors::InvalidName);
     require!(
-        contact_info.len() < 512,
+        contact_info.len() < 126,
         SupplyChainErrors::InvalidContactInfo
     );
     require!(
         transportation_mode.len() < 32,
         SupplyChainErrors::InvalidName
     );
-    require!(
-        warehouse_id == warehouse.warehouse_id,
-        SupplyChainErrors::InvalidWarehouse
-    );
-    require!(
-        product_id == product.product_id && product_id == warehouse.product_id,
-        SupplyChainErrors::InvalidProductId
-    );
     logistics.logistic_id = user.logistics_count + 1;
     logistics.name = name;
     logistics.transportation_mode = transportation_mode;
     logistics.contact_info = contact_info;
-    logistics.product_id = product_id;
-    logistics.delivery_confirmed = false;
-    logistics.product_stock = 0;
     logistics.latitude = latitude;
     logistics.longitude = longitude;
-    user.logistics_count += 1;
     logistics.balance = 0;
-    logistics.warehouse_id = warehouse_id;
+    logistics.shipment_count = 0;
+    logistics.active_shipments = 0;
     logistics.owner = ctx.accounts.owner.key();
+    user.logistics_count += 1;
+
+    emit_cpi!(LogisticsProviderCreated {
+        provider: logistics.key(),
+        logistic_id: logistics.logistic_id,
+        owner: logistics.owner,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateLogisticsContext<'info> {
     #[account(mut)]
@@ -69,16 +65,12 @@ pub struct CreateLogisticsContext<'info> {
     #[account(
         init,
         payer = owner,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Logistics::INIT_SPACE,
+        space = ANCHOR_DISCRIMINATOR_SIZE + LogisticsProvider::INIT_SPACE,
         seeds = [b"logistics",user.key().as_ref(),(user.logistics_count+1).to_le_bytes().as_ref()],
         bump,
     )]
-    pub logistics: Account<'info, Logistics>,
+    pub logistics: Account<'info, LogisticsProvider>,
     #[account(mut)]
     pub user: Account<'info, User>,
-    #[account(mut)]
-    pub warehouse: Account<'info, Warehouse>,
-    #[account(mut)]
-    pub product: Account<'info, Product>,
     pub system_program: Program<'info, System>,
 }
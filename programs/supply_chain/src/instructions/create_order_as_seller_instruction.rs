@@ -1,11 +1,21 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
 use crate::{
-    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    constants::{ANCHOR_DISCRIMINATOR_SIZE, ORDER_SHIPMENT_DEADLINE},
     errors::SupplyChainErrors::*,
+    events::OrderCreated,
+    instructions::token_vault_instruction::transfer_tokens,
     states::{
-        order::Order, product::Product, seller::Seller, transaction::Transaction, user::User,
+        order::{Order, OrderStatus},
+        order_escrow::OrderEscrow,
+        product::Product,
+        program_state::ProgramState,
+        seller::Seller,
+        transaction::Transaction,
+        user::{Role, User},
         warehouse::Warehouse,
+        warehouse_stock::WarehouseStock,
     },
 };
 
@@ -21,7 +31,9 @@ pub fn create_order_instruction_as_seller(
     let transaction = &mut ctx.accounts.transaction;
     let order = &mut ctx.accounts.order;
     let product = &mut ctx.accounts.product;
-    if user.role != "SELLER" {
+    let escrow = &mut ctx.accounts.escrow;
+    let warehouse_stock = &mut ctx.accounts.warehouse_stock;
+    if !user.has_role(Role::Seller) {
         return Err(UnauthorizedAccess.into());
     }
     if ctx.accounts.seller_account.key() != user.owner.key() {
@@ -33,48 +45,86 @@ pub fn create_order_instruction_as_seller(
     if product.product_id != product_id {
         return Err(InvalidProductId.into());
     }
-    if warehouse.product_count < product_stock {
-        return Err(InsufficientStock.into());
+    let now = Clock::get()?.unix_timestamp as u64;
+    if !product.inspection_valid_at(now) {
+        return Err(InspectionNotValid.into());
     }
+    // Reserve the units so concurrent orders cannot oversell the warehouse
+    warehouse_stock.reserve(product_stock)?;
     let total_amount_to_pay = product
         .product_price
         .checked_mul(product_stock)
         .ok_or(Overflow)?;
-    let transaction_instruction = anchor_lang::solana_program::system_instruction::transfer(
-        &ctx.accounts.seller_account.key(),
-        &warehouse.key(),
-        total_amount_to_pay,
-    );
-    let result = anchor_lang::solana_program::program::invoke(
-        &transaction_instruction,
-        &[
-            ctx.accounts.seller_account.to_account_info(),
-            warehouse.to_account_info(),
-        ],
-    );
-    if let Err(e) = result {
-        msg!("Transaction to warehouse failed: {:?}", e);
-        return Err(e.into());
-    }
+    // Payment is held in the order escrow, or its token vault, until the seller confirms receipt
+    let (payment_mint, payment_destination) = match ctx.accounts.payment_mint.as_ref() {
+        None => {
+            let transaction_instruction = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.seller_account.key(),
+                &escrow.key(),
+                total_amount_to_pay,
+            );
+            let result = anchor_lang::solana_program::program::invoke(
+                &transaction_instruction,
+                &[
+                    ctx.accounts.seller_account.to_account_info(),
+                    escrow.to_account_info(),
+                ],
+            );
+            if let Err(e) = result {
+                msg!("Transaction to order escrow failed: {:?}", e);
+                return Err(e.into());
+            }
+            (Pubkey::default(), escrow.key())
+        }
+        Some(payment_mint) => {
+            let (Some(payer_token_account), Some(escrow_vault), Some(token_program)) = (
+                ctx.accounts.payer_token_account.as_ref(),
+                ctx.accounts.escrow_vault.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return Err(InvalidTokenVault.into());
+            };
+            transfer_tokens(
+                payer_token_account,
+                escrow_vault,
+                payment_mint,
+                &ctx.accounts.seller_account,
+                token_program,
+                total_amount_to_pay,
+            )?;
+            (payment_mint.key(), escrow_vault.key())
+        }
+    };
 
     order.order_id = seller.order_count + 1;
     order.product_id = product_id;
     order.product_stock = product_stock;
     order.warehouse_id = warehouse_id;
     order.total_price = total_amount_to_pay;
-    order.timestamp = Clock::get()?.unix_timestamp as u64;
+    order.timestamp = now;
     order.seller_id = seller.seller_id;
-    order.status = String::from("ORDERED");
+    order.status = OrderStatus::Ordered;
+    order.escrow_pda = escrow.key();
+    order.ship_by = now.checked_add(ORDER_SHIPMENT_DEADLINE).ok_or(Overflow)?;
+
+    warehouse_stock.updated_at = now;
+
+    escrow.order_pda = order.key();
+    escrow.seller_pda = seller.key();
+    escrow.warehouse_pda = warehouse.key();
+    escrow.amount = total_amount_to_pay;
+    escrow.payment_mint = payment_mint;
+    escrow.created_at = now;
+    escrow.released = false;
+    escrow.refunded = false;
 
     transaction.transaction_id = user.transaction_count + 1;
     transaction.from = ctx.accounts.seller_account.key();
-    transaction.to = warehouse.key();
+    transaction.to = payment_destination;
     transaction.amount = total_amount_to_pay;
-    transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+    transaction.timestamp = now;
     transaction.status = true;
 
-    warehouse.balance += total_amount_to_pay;
-
     seller.order_count += 1;
     user.transaction_count += 1;
     
@@ -82,9 +132,20 @@ pub fn create_order_instruction_as_seller(
     order.seller_pda = seller.key();
     order.product_pda = product.key();
 
+    emit_cpi!(OrderCreated {
+        order: order.key(),
+        seller: seller.key(),
+        warehouse: warehouse.key(),
+        product: product.key(),
+        quantity: product_stock,
+        total_price: total_amount_to_pay,
+        ship_by: order.ship_by,
+        timestamp: now,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateOrderAsSellerCtx<'info> {
     #[account(mut)]
@@ -96,7 +157,15 @@ pub struct CreateOrderAsSellerCtx<'info> {
         seeds = [b"order",seller.key().as_ref(),(seller.order_count+1).to_le_bytes().as_ref()],
         bump,
     )]
-    pub order: Account<'info, Order>,
+    pub order: Box<Account<'info, Order>>,
+    #[account(
+        init,
+        payer = seller_account,
+        space = ANCHOR_DISCRIMINATOR_SIZE+OrderEscrow::INIT_SPACE,
+        seeds = [b"order_escrow",order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, OrderEscrow>>,
     #[account(
         init,
         payer = seller_account,
@@ -104,14 +173,44 @@ pub struct CreateOrderAsSellerCtx<'info> {
         seeds = [b"transaction",user.key().as_ref(),(user.transaction_count+1).to_le_bytes().as_ref()],
         bump,
     )]
-    pub transaction: Account<'info, Transaction>,
-    #[account(mut)]
-    pub warehouse: Account<'info, Warehouse>,
-    #[account(mut)]
-    pub product: Account<'info, Product>,
-    #[account(mut)]
-    pub user: Account<'info, User>,
+    pub transaction: Box<Account<'info, Transaction>>,
+    #[account(mut, constraint = !warehouse.frozen @ AccountFrozen)]
+    pub warehouse: Box<Account<'info, Warehouse>>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", warehouse.key().as_ref(), product.key().as_ref()],
+        bump,
+    )]
+    pub warehouse_stock: Box<Account<'info, WarehouseStock>>,
+    #[account(mut, constraint = !product.recalled @ ProductRecalled)]
+    pub product: Box<Account<'info, Product>>,
     #[account(mut)]
-    pub seller: Account<'info, Seller>,
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, constraint = !seller.frozen @ AccountFrozen)]
+    pub seller: Box<Account<'info, Seller>>,
+    #[account(seeds = [b"program_state"], bump, constraint = !program_state.paused @ ProgramPaused)]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    // Token accounts below are only required when paying with the payment mint
+    #[account(
+        init,
+        payer = seller_account,
+        seeds = [b"vault", escrow.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = vault_authority,
+    )]
+    pub escrow_vault: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = seller_account,
+    )]
+    pub payer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(address = program_state.payment_mint @ InvalidPaymentMint)]
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
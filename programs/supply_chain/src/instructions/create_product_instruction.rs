@@ -2,6 +2,8 @@ use anchor_lang::prelude::*;
 
 use crate::{
     constants::ANCHOR_DISCRIMINATOR_SIZE,
+    errors::SupplyChainErrors,
+    events::ProductCreated,
     states::{factory::Factory, product::Product},
 };
 
@@ -15,9 +17,11 @@ pub fn create_product(
     raw_material_used: u64,
     product_stock: u64,
     mrp: u64,
+    unit_volume: u64,
 ) -> Result<()> {
     let product = &mut ctx.accounts.product;
     let factory = &mut ctx.accounts.factory;
+    require!(unit_volume > 0, SupplyChainErrors::InvalidUnitVolume);
     product.product_id = factory.product_count + 1;
     product.factory_id = factory.factory_id;
     product.product_name = product_name;
@@ -29,13 +33,25 @@ pub fn create_product(
     product.factory_pda = factory.key();
     product.mrp = mrp;
     product.raw_material_used = raw_material_used;
+    product.unit_volume = unit_volume;
     product.quality_checked = false;
     product.inspection_id = 0;
     product.created_at = Clock::get()?.unix_timestamp as u64;
     factory.product_count += 1;
+
+    emit_cpi!(ProductCreated {
+        product: product.key(),
+        product_id: product.product_id,
+        factory: factory.key(),
+        product_price: product.product_price,
+        product_stock: product.product_stock,
+        mrp: product.mrp,
+        timestamp: product.created_at,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateProductInstructionContext<'info> {
     #[account(mut)]
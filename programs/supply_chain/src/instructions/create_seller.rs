@@ -2,8 +2,9 @@ use anchor_lang::prelude::*;
 
 use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
 use crate::states::seller::Seller;
-use crate::states::user::User;
+use crate::states::user::{Role, User};
 use crate::errors::SupplyChainErrors::*;
+use crate::events::SellerCreated;
 
 pub fn create_seller_instruction(
     ctx: Context<CreateSellerCtx>,
@@ -15,7 +16,7 @@ pub fn create_seller_instruction(
 ) -> Result<()> {    
     let seller = &mut ctx.accounts.seller;
     let user = &mut ctx.accounts.user;
-    if user.role!="SELLER"{
+    if !user.has_role(Role::Seller){
         return Err(UnauthorizedAccess.into());
     }
     if name.len()>32{
@@ -39,9 +40,17 @@ pub fn create_seller_instruction(
     seller.order_count = 0;
     seller.balance = 0;
     user.seller_count+=1;
+
+    emit_cpi!(SellerCreated {
+        seller: seller.key(),
+        seller_id: seller.seller_id,
+        owner: seller.owner,
+        timestamp: seller.registered_at,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateSellerCtx<'info> {
     #[account(mut)]
@@ -1,34 +1,47 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::SupplyChainErrors::*;
-use crate::{constants::ANCHOR_DISCRIMINATOR_SIZE, states::user::User};
+use crate::events::UserCreated;
+use crate::{
+    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    states::{
+        program_state::ProgramState,
+        user::{Role, User},
+    },
+};
 
 pub fn create_user(
     ctx: Context<CreateUserInstructionContext>,
     name: String,
     email: String,
-    role: String,
+    role: Role,
 ) -> Result<()> {
     let user = &mut ctx.accounts.user;
     if name.len() > 32 {
         return Err(InvalidName.into());
     }
-    if role.len() > 32 {
-        return Err(InvalidRole.into());
-    }
     if email.len() > 64 {
         return Err(InvalidEmail.into());
     }
     if user.is_initialized {
         return Err(UserAlreadyInitialized.into());
     }
-    if role == "CUSTOMER" {
-        user.is_customer = true;
+    // Privileged roles need the same platform owner co-signature as add_user_role
+    if role.is_privileged() {
+        let approved = ctx
+            .accounts
+            .authority
+            .as_ref()
+            .is_some_and(|authority| authority.key() == ctx.accounts.program_state.owner);
+        if !approved {
+            return Err(UnauthorizedAccess.into());
+        }
     }
 
     user.name = name;
     user.email = email;
-    user.role = role;
+    user.roles = 0;
+    user.add_role(role);
     user.created_at = Clock::get()?.unix_timestamp as u64;
     user.owner = ctx.accounts.owner.key();
     user.factory_count = 0;
@@ -39,9 +52,17 @@ pub fn create_user(
     user.inspector_count = 0;
     user.seller_count = 0;
     user.is_initialized = true;
+
+    emit_cpi!(UserCreated {
+        user: user.key(),
+        owner: user.owner,
+        roles: user.roles,
+        timestamp: user.created_at,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateUserInstructionContext<'info> {
     #[account(
@@ -54,5 +75,9 @@ pub struct CreateUserInstructionContext<'info> {
     pub user: Account<'info, User>,
     #[account(mut)]
     pub owner: Signer<'info>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    // Platform owner co-signature, only required for a privileged role
+    pub authority: Option<Signer<'info>>,
     pub system_program: Program<'info, System>,
 }
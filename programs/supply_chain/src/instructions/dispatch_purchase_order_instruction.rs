@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    errors::SupplyChainErrors,
+    events::{PlatformFeeCollected, PurchaseOrderStatusChanged, ShipmentDispatched},
+    states::{
+        balance_holder::BalanceHolderKind,
+        factory::Factory,
+        fee_exemption::FeeExemption,
+        logistics_provider::LogisticsProvider,
+        product::Product,
+        program_state::ProgramState,
+        purchase_order::{PurchaseOrder, PurchaseOrderStatus},
+        shipment::{Shipment, ShipmentStatus},
+        transaction::Transaction,
+        treasury::Treasury,
+        user::{Role, User},
+    },
+};
+
+// The factory hands an accepted purchase order to a carrier and pays for the shipment
+pub fn dispatch_purchase_order(
+    ctx: Context<DispatchPurchaseOrderCtx>,
+    logistics_id: u64,
+    shipping_cost: u64,
+) -> Result<()> {
+    let logistics = &mut ctx.accounts.logistics;
+    let user = &mut ctx.accounts.user;
+    let transaction = &mut ctx.accounts.transaction;
+    let purchase_order = &mut ctx.accounts.purchase_order;
+    let shipment = &mut ctx.accounts.shipment;
+    let signer = &ctx.accounts.signer;
+
+    require!(
+        logistics_id == logistics.logistic_id,
+        SupplyChainErrors::InvalidLogistics
+    );
+    require!(
+        user.has_role(Role::Factory) && user.owner == signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        ctx.accounts.factory.owner == signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    purchase_order.transition_to(PurchaseOrderStatus::Shipped)?;
+
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        ctx.accounts
+            .program_state
+            .purchase_fee_for(BalanceHolderKind::Logistics, shipping_cost)?
+    };
+    let logistics_amount = shipping_cost - platform_fee;
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: signer.to_account_info(),
+                to: logistics.to_account_info(),
+            },
+        ),
+        logistics_amount,
+    )?;
+    if platform_fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: signer.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            platform_fee,
+        )?;
+    }
+    let now = Clock::get()?.unix_timestamp as u64;
+    transaction.transaction_id = user.transaction_count + 1;
+    transaction.from = signer.key();
+    transaction.to = logistics.key();
+    transaction.amount = shipping_cost;
+    transaction.timestamp = now;
+    transaction.status = true;
+
+    shipment.shipment_id = logistics.shipment_count + 1;
+    shipment.order_pda = purchase_order.key();
+    shipment.provider_pda = logistics.key();
+    shipment.warehouse_pda = purchase_order.warehouse_pda;
+    shipment.seller_pda = Pubkey::default();
+    shipment.product_id = ctx.accounts.product.product_id;
+    shipment.product_pda = purchase_order.product_pda;
+    shipment.product_stock = purchase_order.quantity;
+    shipment.shipment_cost = shipping_cost;
+    shipment.status = ShipmentStatus::InTransit;
+    // The factory keeps custody until it hands the goods to the carrier
+    shipment.custodian = signer.key();
+    shipment.checkpoint_count = 0;
+    shipment.shipment_started_at = now;
+    shipment.shipment_ended_at = 0;
+
+    logistics.balance += logistics_amount;
+    logistics.shipment_count += 1;
+    logistics.active_shipments += 1;
+
+    purchase_order.logistic_pda = logistics.key();
+    purchase_order.shipment_pda = shipment.key();
+    purchase_order.updated_at = now;
+
+    user.transaction_count += 1;
+
+    emit_cpi!(PurchaseOrderStatusChanged {
+        purchase_order: purchase_order.key(),
+        status: purchase_order.status,
+        timestamp: now,
+    });
+    if platform_fee > 0 {
+        emit_cpi!(PlatformFeeCollected {
+            payer: signer.key(),
+            payee: logistics.key(),
+            amount: platform_fee,
+            timestamp: now,
+        });
+    }
+    emit_cpi!(ShipmentDispatched {
+        shipment: shipment.key(),
+        order: purchase_order.key(),
+        provider: logistics.key(),
+        warehouse: purchase_order.warehouse_pda,
+        quantity: shipment.product_stock,
+        shipment_cost: shipping_cost,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DispatchPurchaseOrderCtx<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub purchase_order: Box<Account<'info, PurchaseOrder>>,
+    #[account(
+        address = purchase_order.factory_pda,
+        constraint = !factory.frozen @ SupplyChainErrors::AccountFrozen,
+    )]
+    pub factory: Box<Account<'info, Factory>>,
+    #[account(address = purchase_order.product_pda)]
+    pub product: Box<Account<'info, Product>>,
+    #[account(mut, constraint = !logistics.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub logistics: Box<Account<'info, LogisticsProvider>>,
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Shipment::INIT_SPACE,
+        seeds = [b"shipment", purchase_order.key().as_ref()],
+        bump,
+    )]
+    pub shipment: Box<Account<'info, Shipment>>,
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [b"transaction", user.key().as_ref(), (user.transaction_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub transaction: Box<Account<'info, Transaction>>,
+    #[account(mut)]
+    pub user: Box<Account<'info, User>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", logistics.owner.as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
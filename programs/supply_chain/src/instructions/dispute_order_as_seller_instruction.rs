@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::events::OrderStatusChanged;
+use crate::states::{
+    order::{Order, OrderStatus},
+    seller::Seller,
+    user::{Role, User},
+};
+
+pub fn dispute_order_instruction_as_seller(ctx: Context<DisputeOrderAsSellerCtx>) -> Result<()> {
+    let user = &ctx.accounts.user;
+    let seller = &ctx.accounts.seller;
+    let order = &mut ctx.accounts.order;
+    let signer = ctx.accounts.seller_account.key();
+
+    require!(
+        user.has_role(Role::Seller) && user.owner == signer,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        seller.owner == signer,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        order.seller_pda == seller.key(),
+        SupplyChainErrors::InvalidOrder
+    );
+    // Only shipped goods can be disputed, the platform owner then refunds the escrow or
+    // releases it to the warehouse
+    order.transition_to(OrderStatus::Disputed)?;
+
+    emit_cpi!(OrderStatusChanged {
+        order: order.key(),
+        status: order.status,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DisputeOrderAsSellerCtx<'info> {
+    pub seller_account: Signer<'info>,
+    #[account(mut)]
+    pub order: Account<'info, Order>,
+    pub seller: Account<'info, Seller>,
+    pub user: Account<'info, User>,
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::errors::SupplyChainErrors;
+use crate::events::FeeExemptionChanged;
+use crate::states::{fee_exemption::FeeExemption, program_state::ProgramState};
+
+pub fn add_fee_exemption(ctx: Context<AddFeeExemptionCtx>) -> Result<()> {
+    require!(
+        ctx.accounts.program_state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let exemption = &mut ctx.accounts.fee_exemption;
+    exemption.account = ctx.accounts.partner.key();
+    exemption.added_by = ctx.accounts.owner.key();
+    exemption.created_at = Clock::get()?.unix_timestamp as u64;
+
+    emit_cpi!(FeeExemptionChanged {
+        account: exemption.account,
+        exempt: true,
+        timestamp: exemption.created_at,
+    });
+    Ok(())
+}
+
+pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemptionCtx>) -> Result<()> {
+    require!(
+        ctx.accounts.program_state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+
+    emit_cpi!(FeeExemptionChanged {
+        account: ctx.accounts.fee_exemption.account,
+        exempt: false,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddFeeExemptionCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: Wallet of the partner that is exempted from platform fees
+    pub partner: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + FeeExemption::INIT_SPACE,
+        seeds = [b"fee_exemption", partner.key().as_ref()],
+        bump,
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveFeeExemptionCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"fee_exemption", fee_exemption.account.as_ref()],
+        bump,
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::errors::SupplyChainErrors;
+use crate::events::CustodyTransferred;
+use crate::states::{
+    shipment::{Shipment, ShipmentStatus},
+    shipment_checkpoint::{CheckpointKind, ShipmentCheckpoint},
+};
+
+pub fn handoff_custody(
+    ctx: Context<HandoffCustodyCtx>,
+    location: String,
+    latitude: f64,
+    longitude: f64,
+    condition: String,
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let checkpoint = &mut ctx.accounts.checkpoint;
+    let outgoing = ctx.accounts.outgoing_custodian.key();
+    let incoming = ctx.accounts.incoming_custodian.key();
+
+    require!(
+        shipment.status == ShipmentStatus::InTransit,
+        SupplyChainErrors::ShipmentNotInTransit
+    );
+    // Both parties sign, so the custody chain can not be forged by either side alone
+    require!(
+        shipment.custodian == outgoing && outgoing != incoming,
+        SupplyChainErrors::InvalidCustodian
+    );
+    require!(location.len() <= 64, SupplyChainErrors::InvalidLocation);
+    require!(condition.len() <= 128, SupplyChainErrors::InvalidCondition);
+
+    checkpoint.checkpoint_id = shipment.checkpoint_count + 1;
+    checkpoint.shipment_pda = shipment.key();
+    checkpoint.kind = CheckpointKind::Handoff;
+    checkpoint.recorded_by = outgoing;
+    checkpoint.from_custodian = outgoing;
+    checkpoint.to_custodian = incoming;
+    checkpoint.location = location;
+    checkpoint.latitude = latitude;
+    checkpoint.longitude = longitude;
+    checkpoint.condition = condition;
+    checkpoint.timestamp = Clock::get()?.unix_timestamp as u64;
+
+    shipment.custodian = incoming;
+    shipment.checkpoint_count += 1;
+
+    emit_cpi!(CustodyTransferred {
+        shipment: shipment.key(),
+        checkpoint: checkpoint.key(),
+        from_custodian: outgoing,
+        to_custodian: incoming,
+        timestamp: checkpoint.timestamp,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct HandoffCustodyCtx<'info> {
+    #[account(mut)]
+    pub outgoing_custodian: Signer<'info>,
+    pub incoming_custodian: Signer<'info>,
+    #[account(mut)]
+    pub shipment: Account<'info, Shipment>,
+    #[account(
+        init,
+        payer = outgoing_custodian,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ShipmentCheckpoint::INIT_SPACE,
+        seeds = [b"checkpoint", shipment.key().as_ref(), (shipment.checkpoint_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, ShipmentCheckpoint>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,8 +1,14 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::constants::{
+    ANCHOR_DISCRIMINATOR_SIZE, DEFAULT_MIN_WITHDRAW_AMOUNT, DEFAULT_WITHDRAW_FEE_BPS,
+};
 use crate::errors::SupplyChainErrors;
-use crate::states::program_state::ProgramState;
+use crate::events::ProgramInitialized;
+use crate::states::{
+    program_state::{FeeSchedule, ProgramState},
+    treasury::Treasury,
+};
 
 pub fn initialize_program_state(
     ctx: Context<InitializeProgramCtx>
@@ -14,11 +20,28 @@ pub fn initialize_program_state(
     );
 
     program_state.owner = ctx.accounts.owner.key();
-    program_state.platform_fee = 2;
+    // Every role starts with a 2% withdraw fee and no purchase fee
+    let default_fees = FeeSchedule {
+        withdraw_fee_bps: DEFAULT_WITHDRAW_FEE_BPS,
+        purchase_fee_bps: 0,
+    };
+    program_state.factory_fees = default_fees;
+    program_state.warehouse_fees = default_fees;
+    program_state.seller_fees = default_fees;
+    program_state.logistics_fees = default_fees;
+    program_state.inspector_fees = default_fees;
+    program_state.min_withdraw_amount = DEFAULT_MIN_WITHDRAW_AMOUNT;
     program_state.initialized = true;
+    ctx.accounts.treasury.created_at = Clock::get()?.unix_timestamp as u64;
+
+    emit_cpi!(ProgramInitialized {
+        owner: program_state.owner,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializeProgramCtx<'info> {
     #[account(
@@ -29,6 +52,14 @@ pub struct InitializeProgramCtx<'info> {
         bump,
     )]
     pub program_state: Account<'info, ProgramState>,
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
+        bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::MIN_INSPECTOR_STAKE,
+    errors::SupplyChainErrors,
+    events::{BatchInspected, InspectorPaid, PlatformFeeCollected},
+    states::{
+        balance_holder::BalanceHolderKind,
+        batch::Batch,
+        fee_exemption::FeeExemption,
+        inspection::InspectionOutcome,
+        inspection_request::{InspectionRequest, InspectionRequestStatus},
+        inspector_profile::InspectorProfile,
+        inspector_stake::InspectorStake,
+        product::Product,
+        program_state::ProgramState,
+        treasury::Treasury,
+        user::{Role, User},
+    },
+};
+
+// Completes an accepted inspection request for a restocked batch. A passing outcome releases
+// the batch for sale by adding its units to the product stock, a failing one keeps it locked
+pub fn inspect_batch(ctx: Context<InspectBatchCtx>, outcome: InspectionOutcome) -> Result<()> {
+    let batch = &mut ctx.accounts.batch;
+    let product = &mut ctx.accounts.product;
+    let inspector = &mut ctx.accounts.inspector;
+    let request = &mut ctx.accounts.inspection_request;
+    require!(
+        ctx.accounts.user.has_role(Role::Inspector),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        outcome.is_valid(),
+        SupplyChainErrors::InvalidInspectionOutcome
+    );
+    require!(!batch.inspected, SupplyChainErrors::QualityChecked);
+    let now = Clock::get()?.unix_timestamp as u64;
+    require!(now < batch.expires_at, SupplyChainErrors::BatchExpired);
+
+    batch.inspected = true;
+    batch.passed = outcome.is_passing();
+    batch.inspector_pda = inspector.key();
+    batch.inspected_at = now;
+    if batch.passed {
+        batch.remaining = batch.quantity;
+        product.product_stock = product
+            .product_stock
+            .checked_add(batch.quantity)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        product.batch_stock = product
+            .batch_stock
+            .checked_add(batch.quantity)
+            .ok_or(SupplyChainErrors::Overflow)?;
+    }
+
+    inspector.inspection_count = inspector
+        .inspection_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    inspector.requests_completed = inspector
+        .requests_completed
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    inspector.total_turnaround = inspector
+        .total_turnaround
+        .checked_add(now.saturating_sub(request.created_at))
+        .ok_or(SupplyChainErrors::Overflow)?;
+    inspector.refresh_reputation();
+    // The stake backs the batch until it can no longer be sold
+    let stake = &mut ctx.accounts.stake;
+    stake.locked_until = stake.locked_until.max(batch.expires_at);
+
+    request.transition_to(InspectionRequestStatus::Completed)?;
+    request.inspection_pda = batch.key();
+    request.completed_at = now;
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        ctx.accounts
+            .program_state
+            .purchase_fee_for(BalanceHolderKind::Inspector, request.fee)?
+    };
+    let inspector_amount = request.fee - platform_fee;
+    **request.to_account_info().try_borrow_mut_lamports()? -= request.fee;
+    **inspector.to_account_info().try_borrow_mut_lamports()? += inspector_amount;
+    **ctx
+        .accounts
+        .treasury
+        .to_account_info()
+        .try_borrow_mut_lamports()? += platform_fee;
+    inspector.balance = inspector
+        .balance
+        .checked_add(inspector_amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    request.released = inspector_amount;
+
+    emit_cpi!(BatchInspected {
+        batch: batch.key(),
+        product: product.key(),
+        inspector: inspector.key(),
+        quantity: batch.quantity,
+        passed: batch.passed,
+        timestamp: now,
+    });
+    if platform_fee > 0 {
+        emit_cpi!(PlatformFeeCollected {
+            payer: request.key(),
+            payee: inspector.key(),
+            amount: platform_fee,
+            timestamp: now,
+        });
+    }
+    emit_cpi!(InspectorPaid {
+        product: product.key(),
+        inspector: inspector.key(),
+        payer: request.requested_by,
+        amount: request.fee,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InspectBatchCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"user", owner.key().as_ref()], bump)]
+    pub user: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [b"inspector_profile", owner.key().as_ref()],
+        bump,
+        constraint = !inspector.frozen @ SupplyChainErrors::AccountFrozen,
+    )]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(
+        mut,
+        seeds = [b"inspector_stake", inspector.key().as_ref()],
+        bump,
+        constraint = stake.amount >= MIN_INSPECTOR_STAKE @ SupplyChainErrors::InsufficientInspectorStake,
+    )]
+    pub stake: Account<'info, InspectorStake>,
+    #[account(
+        mut,
+        seeds = [b"inspection_request", product.key().as_ref(), inspection_request.request_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = inspection_request.inspector_pda == inspector.key() @ SupplyChainErrors::InvalidInspectionRequest,
+    )]
+    pub inspection_request: Account<'info, InspectionRequest>,
+    #[account(
+        mut,
+        seeds = [b"batch", product.key().as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub batch: Account<'info, Batch>,
+    #[account(mut, address = batch.product_pda)]
+    pub product: Account<'info, Product>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", owner.key().as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
+}
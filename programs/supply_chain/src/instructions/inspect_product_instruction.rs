@@ -1,79 +1,226 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::constants::{
+    ANCHOR_DISCRIMINATOR_SIZE, MAX_INSPECTION_VALIDITY_PERIOD, MIN_INSPECTOR_STAKE,
+};
 use crate::errors::SupplyChainErrors::*;
+use crate::events::{InspectorPaid, PlatformFeeCollected, ProductInspected};
 use crate::states::factory::Factory;
-use crate::states::{product::Product, product_inspector::ProductInspector, user::User};
+use crate::states::{
+    balance_holder::BalanceHolderKind,
+    fee_exemption::FeeExemption,
+    inspection::{Inspection, InspectionOutcome, InspectionScores},
+    inspection_request::{InspectionRequest, InspectionRequestStatus},
+    inspector_profile::InspectorProfile,
+    inspector_stake::InspectorStake,
+    product::Product,
+    program_state::ProgramState,
+    treasury::Treasury,
+    user::{Role, User},
+};
 
 pub fn inspect_product_instruction(
     ctx: Context<InspectProductContext>,
-    name: String,
-    latitude: f64,
-    longitude: f64,
     product_id: u64,
-    inspection_outcome: String,
+    outcome: InspectionOutcome,
+    scores: InspectionScores,
     notes: String,
-    fee_charge_per_product: u64,
+    valid_until: u64,
 ) -> Result<()> {
-    let user = &mut ctx.accounts.user;
-    let inspector = &mut ctx.accounts.inspection_details;
+    if ctx.accounts.product.quality_checked {
+        return Err(QualityChecked.into());
+    }
+    submit_inspection(ctx, product_id, outcome, scores, notes, valid_until)
+}
+
+// Renews or replaces the current inspection of a product, the new record links back to the
+// one it supersedes so the full inspection history stays on chain
+pub fn reinspect_product_instruction(
+    ctx: Context<InspectProductContext>,
+    product_id: u64,
+    outcome: InspectionOutcome,
+    scores: InspectionScores,
+    notes: String,
+    valid_until: u64,
+) -> Result<()> {
+    if ctx.accounts.product.inspection_id == 0 {
+        return Err(ProductNotQualityChecked.into());
+    }
+    submit_inspection(ctx, product_id, outcome, scores, notes, valid_until)
+}
+
+fn submit_inspection(
+    ctx: Context<InspectProductContext>,
+    product_id: u64,
+    outcome: InspectionOutcome,
+    scores: InspectionScores,
+    notes: String,
+    valid_until: u64,
+) -> Result<()> {
+    let user = &ctx.accounts.user;
+    let inspector = &mut ctx.accounts.inspector;
+    let inspection = &mut ctx.accounts.inspection;
     let product = &mut ctx.accounts.product;
-    let factory = &mut ctx.accounts.factory;
-    if user.role != "INSPECTOR" {
+    let request = &mut ctx.accounts.inspection_request;
+    if !user.has_role(Role::Inspector) {
         return Err(UnauthorizedAccess.into());
     }
-    if factory.factory_id != product.factory_id {
-        return Err(InvalidFactory.into());
-    }
     if product_id != product.product_id {
         return Err(InvalidProductId.into());
     }
-    if name.len() > 32 {
-        return Err(InvalidName.into());
-    }
-    if product.quality_checked {
-        return Err(QualityChecked.into());
-    }
-    if inspection_outcome.len() > 120 {
+    if !outcome.is_valid() {
         return Err(InvalidInspectionOutcome.into());
     }
+    if !scores.is_valid() {
+        return Err(InvalidInspectionScore.into());
+    }
     if notes.len() > 512 {
         return Err(InvalidNotes.into());
     }
-    inspector.inspector_id = user.inspector_count + 1;
-    inspector.name = name;
-    inspector.latitude = latitude;
-    inspector.longitude = longitude;
-    inspector.product_id = product_id;
-    inspector.inspection_outcome = inspection_outcome;
-    inspector.notes = notes;
-    inspector.inspection_date = Clock::get()?.unix_timestamp as u64;
-    inspector.fee_charge_per_product = fee_charge_per_product;
-    inspector.owner = ctx.accounts.owner.key();
-    inspector.balance = 0;
-    user.inspector_count += 1;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let max_valid_until = now
+        .checked_add(MAX_INSPECTION_VALIDITY_PERIOD)
+        .ok_or(Overflow)?;
+    if valid_until <= now || valid_until > max_valid_until {
+        return Err(InvalidInspectionValidity.into());
+    }
+    inspection.inspection_id = inspector.inspection_count.checked_add(1).ok_or(Overflow)?;
+    inspection.inspector_pda = inspector.key();
+    inspection.product_id = product_id;
+    inspection.product_pda = product.key();
+    inspection.outcome = outcome;
+    inspection.scores = scores;
+    inspection.notes = notes;
+    inspection.fee_charge_per_product = inspector.fee_charge_per_product;
+    inspection.inspected_at = now;
+    inspection.valid_until = valid_until;
+    inspection.previous_inspection = if product.inspection_id == 0 {
+        Pubkey::default()
+    } else {
+        Pubkey::find_program_address(
+            &[
+                b"inspection",
+                product.inspector_pda.as_ref(),
+                product.inspection_id.to_le_bytes().as_ref(),
+            ],
+            ctx.program_id,
+        )
+        .0
+    };
+    inspector.inspection_count = inspection.inspection_id;
+    inspector.requests_completed = inspector.requests_completed.checked_add(1).ok_or(Overflow)?;
+    inspector.total_turnaround = inspector
+        .total_turnaround
+        .checked_add(now.saturating_sub(request.created_at))
+        .ok_or(Overflow)?;
+    inspector.refresh_reputation();
+    // The stake backs this inspection for as long as it unlocks purchases
+    let stake = &mut ctx.accounts.stake;
+    stake.locked_until = stake.locked_until.max(inspection.valid_until);
 
-    product.inspection_id = inspector.inspector_id;
-    product.quality_checked = true;
+    product.inspection_id = inspection.inspection_id;
+    // A failed inspection is recorded but leaves the product locked for sale
+    product.quality_checked = inspection.outcome.is_passing();
     product.inspector_pda = inspector.key();
+    product.inspection_valid_until = inspection.valid_until;
+
+    // Submitting the inspection completes the request and releases its escrowed fee
+    request.transition_to(InspectionRequestStatus::Completed)?;
+    request.inspection_pda = inspection.key();
+    request.completed_at = inspection.inspected_at;
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        ctx.accounts
+            .program_state
+            .purchase_fee_for(BalanceHolderKind::Inspector, request.fee)?
+    };
+    let inspector_amount = request.fee - platform_fee;
+    **request.to_account_info().try_borrow_mut_lamports()? -= request.fee;
+    **inspector.to_account_info().try_borrow_mut_lamports()? += inspector_amount;
+    **ctx
+        .accounts
+        .treasury
+        .to_account_info()
+        .try_borrow_mut_lamports()? += platform_fee;
+    inspector.balance = inspector
+        .balance
+        .checked_add(inspector_amount)
+        .ok_or(Overflow)?;
+    request.released = inspector_amount;
+    product.inspection_fee_paid = true;
+
+    emit_cpi!(ProductInspected {
+        product: product.key(),
+        inspection: inspection.key(),
+        inspector: inspector.key(),
+        inspection_id: inspection.inspection_id,
+        passed: product.quality_checked,
+        fee_charge_per_product: inspection.fee_charge_per_product,
+        timestamp: inspection.inspected_at,
+    });
+    if platform_fee > 0 {
+        emit_cpi!(PlatformFeeCollected {
+            payer: request.key(),
+            payee: inspector.key(),
+            amount: platform_fee,
+            timestamp: inspection.inspected_at,
+        });
+    }
+    emit_cpi!(InspectorPaid {
+        product: product.key(),
+        inspector: inspector.key(),
+        payer: request.requested_by,
+        amount: request.fee,
+        timestamp: inspection.inspected_at,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InspectProductContext<'info> {
     #[account(
         init,
         payer = owner,
-        space = ANCHOR_DISCRIMINATOR_SIZE+ProductInspector::INIT_SPACE,
-        seeds = [b"product_inspector",user.key().as_ref(),(user.inspector_count+1).to_le_bytes().as_ref()],
+        space = ANCHOR_DISCRIMINATOR_SIZE + Inspection::INIT_SPACE,
+        seeds = [b"inspection", inspector.key().as_ref(), (inspector.inspection_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub inspection: Account<'info, Inspection>,
+    #[account(
+        mut,
+        seeds = [b"inspector_profile", owner.key().as_ref()],
         bump,
+        constraint = !inspector.frozen @ AccountFrozen,
     )]
-    pub inspection_details: Account<'info, ProductInspector>,
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(
+        mut,
+        seeds = [b"inspector_stake", inspector.key().as_ref()],
+        bump,
+        constraint = stake.amount >= MIN_INSPECTOR_STAKE @ InsufficientInspectorStake,
+    )]
+    pub stake: Account<'info, InspectorStake>,
+    #[account(
+        mut,
+        seeds = [b"inspection_request", product.key().as_ref(), inspection_request.request_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = inspection_request.inspector_pda == inspector.key() @ InvalidInspectionRequest,
+    )]
+    pub inspection_request: Account<'info, InspectionRequest>,
+    #[account(seeds = [b"program_state"], bump, constraint = !program_state.paused @ ProgramPaused)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", owner.key().as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
     #[account(mut)]
     pub product: Account<'info, Product>,
-    #[account(mut)]
+    #[account(address = product.factory_pda @ InvalidFactory)]
     pub factory: Account<'info, Factory>,
-    #[account(mut)]
+    #[account(seeds = [b"user", owner.key().as_ref()], bump)]
     pub user: Account<'info, User>,
     #[account(mut)]
     pub owner: Signer<'info>,
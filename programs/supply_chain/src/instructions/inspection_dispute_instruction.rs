@@ -0,0 +1,432 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    constants::{
+        ANCHOR_DISCRIMINATOR_SIZE, BPS_DENOMINATOR, DISPUTE_REVIEW_PERIOD,
+        MIN_DISPUTE_REVIEW_FEE_BPS, MIN_INSPECTOR_STAKE,
+    },
+    errors::SupplyChainErrors,
+    events::{
+        DisputeReviewerAssigned, InspectionDisputeClosed, InspectionDisputeResolved,
+        InspectionDisputed,
+    },
+    states::{
+        customer_product::CustomerProduct,
+        inspection::{Inspection, InspectionOutcome, InspectionScores},
+        inspection_dispute::{InspectionDispute, InspectionDisputeStatus},
+        inspection_request::InspectionRequest,
+        inspector_profile::InspectorProfile,
+        inspector_stake::InspectorStake,
+        product::Product,
+        program_state::ProgramState,
+        user::{Role, User},
+    },
+};
+
+// Raised by the factory that ordered the inspection or by a customer holding the product.
+// The inspector's payout is pulled back from its balance, and from its stake if it was
+// already withdrawn, and held with the review fee until the dispute is settled
+pub fn dispute_inspection(
+    ctx: Context<DisputeInspectionCtx>,
+    reason: String,
+    review_fee: u64,
+) -> Result<()> {
+    let request = &ctx.accounts.inspection_request;
+    let inspector = &mut ctx.accounts.inspector;
+    let stake = &mut ctx.accounts.stake;
+    let dispute = &mut ctx.accounts.dispute;
+    let raiser = ctx.accounts.raiser.key();
+    let product = ctx.accounts.product.key();
+    let by_factory = request.requested_by == raiser;
+    let by_customer = ctx
+        .accounts
+        .customer_product
+        .as_ref()
+        .is_some_and(|holding| {
+            holding.owner == raiser && holding.product_pda == product && holding.stock_quantity > 0
+        });
+    require!(
+        by_factory || by_customer,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(reason.len() <= 256, SupplyChainErrors::InvalidDisputeReason);
+    require!(review_fee > 0, SupplyChainErrors::InvalidInspectionFee);
+    // Never above the request fee, so the cast back cannot truncate
+    let min_review_fee =
+        (request.fee as u128 * MIN_DISPUTE_REVIEW_FEE_BPS as u128 / BPS_DENOMINATOR as u128) as u64;
+    require!(
+        review_fee >= min_review_fee,
+        SupplyChainErrors::ReviewFeeTooLow
+    );
+    let now = Clock::get()?.unix_timestamp as u64;
+    require!(
+        now < ctx.accounts.inspection.valid_until,
+        SupplyChainErrors::InspectionNotValid
+    );
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.raiser.to_account_info(),
+                to: dispute.to_account_info(),
+            },
+        ),
+        review_fee,
+    )?;
+
+    let frozen_from_balance = request.released.min(inspector.balance);
+    let frozen_from_stake = request.released - frozen_from_balance;
+    require!(
+        frozen_from_stake <= stake.amount,
+        SupplyChainErrors::InsufficientInspectorStake
+    );
+    **inspector.to_account_info().try_borrow_mut_lamports()? -= frozen_from_balance;
+    **stake.to_account_info().try_borrow_mut_lamports()? -= frozen_from_stake;
+    **dispute.to_account_info().try_borrow_mut_lamports()? += request.released;
+    inspector.balance -= frozen_from_balance;
+    stake.amount -= frozen_from_stake;
+
+    dispute.inspection_pda = ctx.accounts.inspection.key();
+    dispute.request_pda = request.key();
+    dispute.product_pda = product;
+    dispute.inspector_pda = inspector.key();
+    dispute.raised_by = raiser;
+    // The factory paid the inspection fee, so it gets the payout back if the review overturns
+    // the result, even when a customer raised the dispute
+    dispute.refund_to = request.requested_by;
+    dispute.reason = reason;
+    dispute.frozen_from_balance = frozen_from_balance;
+    dispute.frozen_from_stake = frozen_from_stake;
+    dispute.review_fee = review_fee;
+    dispute.status = InspectionDisputeStatus::Open;
+    dispute.created_at = now;
+    dispute.review_by = now
+        .checked_add(DISPUTE_REVIEW_PERIOD)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(InspectionDisputed {
+        dispute: dispute.key(),
+        inspection: dispute.inspection_pda,
+        raised_by: raiser,
+        frozen: dispute.frozen(),
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// The platform owner picks an independent, staked inspector to re-inspect the product
+pub fn assign_dispute_reviewer(ctx: Context<AssignDisputeReviewerCtx>) -> Result<()> {
+    require!(
+        ctx.accounts.program_state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let dispute = &mut ctx.accounts.dispute;
+    let reviewer = &ctx.accounts.reviewer;
+    require!(
+        reviewer.key() != dispute.inspector_pda,
+        SupplyChainErrors::ReviewerNotIndependent
+    );
+    dispute.transition_to(InspectionDisputeStatus::UnderReview)?;
+    dispute.reviewer_pda = reviewer.key();
+
+    emit_cpi!(DisputeReviewerAssigned {
+        dispute: dispute.key(),
+        reviewer: reviewer.key(),
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+// The reviewer's inspection settles the dispute: if it agrees with the original result the
+// frozen payout goes back to the inspector, otherwise it is refunded to the factory and the
+// product follows the review unless it was re-inspected since. The reviewer is paid the
+// review fee either way
+pub fn resolve_inspection_dispute(
+    ctx: Context<ResolveInspectionDisputeCtx>,
+    outcome: InspectionOutcome,
+    scores: InspectionScores,
+    notes: String,
+) -> Result<()> {
+    let dispute = &mut ctx.accounts.dispute;
+    let reviewer = &mut ctx.accounts.reviewer;
+    let review = &mut ctx.accounts.review_inspection;
+    let product = &mut ctx.accounts.product;
+    require!(
+        ctx.accounts.user.has_role(Role::Inspector),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        outcome.is_valid(),
+        SupplyChainErrors::InvalidInspectionOutcome
+    );
+    require!(scores.is_valid(), SupplyChainErrors::InvalidInspectionScore);
+    require!(notes.len() <= 512, SupplyChainErrors::InvalidNotes);
+    let now = Clock::get()?.unix_timestamp as u64;
+
+    review.inspection_id = reviewer
+        .inspection_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    review.inspector_pda = reviewer.key();
+    review.product_id = product.product_id;
+    review.product_pda = product.key();
+    review.outcome = outcome;
+    review.scores = scores;
+    review.notes = notes;
+    review.fee_charge_per_product = reviewer.fee_charge_per_product;
+    review.inspected_at = now;
+    // The review covers the same goods, so it expires with the inspection it replaces
+    review.valid_until = ctx.accounts.original_inspection.valid_until;
+    review.previous_inspection = dispute.inspection_pda;
+    reviewer.inspection_count = review.inspection_id;
+    reviewer.refresh_reputation();
+    let reviewer_stake = &mut ctx.accounts.reviewer_stake;
+    reviewer_stake.locked_until = reviewer_stake.locked_until.max(review.valid_until);
+
+    let upheld =
+        review.outcome.is_passing() == ctx.accounts.original_inspection.outcome.is_passing();
+    dispute.transition_to(if upheld {
+        InspectionDisputeStatus::Upheld
+    } else {
+        InspectionDisputeStatus::Overturned
+    })?;
+    dispute.review_inspection_pda = review.key();
+    dispute.resolved_at = now;
+
+    let frozen = dispute.frozen();
+    **dispute.to_account_info().try_borrow_mut_lamports()? -= frozen + dispute.review_fee;
+    if upheld {
+        release_frozen_payout(
+            dispute,
+            &mut ctx.accounts.inspector,
+            &mut ctx.accounts.stake,
+        )?;
+    } else {
+        let inspector = &mut ctx.accounts.inspector;
+        inspector.disputes_lost = inspector
+            .disputes_lost
+            .checked_add(1)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        inspector.refresh_reputation();
+        **ctx
+            .accounts
+            .refund_to
+            .to_account_info()
+            .try_borrow_mut_lamports()? += frozen;
+        let original = &ctx.accounts.original_inspection;
+        if product.inspection_id == original.inspection_id
+            && product.inspector_pda == original.inspector_pda
+        {
+            product.inspection_id = review.inspection_id;
+            product.inspector_pda = reviewer.key();
+            product.quality_checked = review.outcome.is_passing();
+            product.inspection_valid_until = review.valid_until;
+        }
+    }
+    **reviewer.to_account_info().try_borrow_mut_lamports()? += dispute.review_fee;
+    reviewer.balance = reviewer
+        .balance
+        .checked_add(dispute.review_fee)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(InspectionDisputeResolved {
+        dispute: dispute.key(),
+        review_inspection: review.key(),
+        upheld,
+        amount: frozen,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// The raiser can withdraw a dispute while no reviewer has taken it
+pub fn cancel_inspection_dispute(ctx: Context<CancelInspectionDisputeCtx>) -> Result<()> {
+    close_inspection_dispute(
+        &mut ctx.accounts.dispute,
+        &mut ctx.accounts.inspector,
+        &mut ctx.accounts.stake,
+        &ctx.accounts.raiser.to_account_info(),
+        InspectionDisputeStatus::Cancelled,
+    )?;
+    emit_cpi!(InspectionDisputeClosed {
+        dispute: ctx.accounts.dispute.key(),
+        status: InspectionDisputeStatus::Cancelled,
+        released: ctx.accounts.dispute.frozen(),
+        timestamp: ctx.accounts.dispute.resolved_at,
+    });
+    Ok(())
+}
+
+// Once the review period is over without a result anyone can expire the dispute, so the
+// inspector is not kept from its payout by a reviewer that never reports back
+pub fn expire_inspection_dispute(ctx: Context<ExpireInspectionDisputeCtx>) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp as u64 >= ctx.accounts.dispute.review_by,
+        SupplyChainErrors::DisputeReviewPeriodNotOver
+    );
+    close_inspection_dispute(
+        &mut ctx.accounts.dispute,
+        &mut ctx.accounts.inspector,
+        &mut ctx.accounts.stake,
+        &ctx.accounts.raised_by.to_account_info(),
+        InspectionDisputeStatus::Expired,
+    )?;
+    emit_cpi!(InspectionDisputeClosed {
+        dispute: ctx.accounts.dispute.key(),
+        status: InspectionDisputeStatus::Expired,
+        released: ctx.accounts.dispute.frozen(),
+        timestamp: ctx.accounts.dispute.resolved_at,
+    });
+    Ok(())
+}
+
+// Ends a dispute without a review: the frozen payout goes back to the inspector and the
+// unused review fee back to the raiser
+fn close_inspection_dispute<'info>(
+    dispute: &mut Account<'info, InspectionDispute>,
+    inspector: &mut Account<'info, InspectorProfile>,
+    stake: &mut Account<'info, InspectorStake>,
+    raiser: &AccountInfo<'info>,
+    status: InspectionDisputeStatus,
+) -> Result<()> {
+    dispute.transition_to(status)?;
+    dispute.resolved_at = Clock::get()?.unix_timestamp as u64;
+    **dispute.to_account_info().try_borrow_mut_lamports()? -= dispute.frozen() + dispute.review_fee;
+    release_frozen_payout(dispute, inspector, stake)?;
+    **raiser.try_borrow_mut_lamports()? += dispute.review_fee;
+    Ok(())
+}
+
+fn release_frozen_payout<'info>(
+    dispute: &InspectionDispute,
+    inspector: &mut Account<'info, InspectorProfile>,
+    stake: &mut Account<'info, InspectorStake>,
+) -> Result<()> {
+    **inspector.to_account_info().try_borrow_mut_lamports()? += dispute.frozen_from_balance;
+    **stake.to_account_info().try_borrow_mut_lamports()? += dispute.frozen_from_stake;
+    inspector.balance = inspector
+        .balance
+        .checked_add(dispute.frozen_from_balance)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    stake.amount = stake
+        .amount
+        .checked_add(dispute.frozen_from_stake)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DisputeInspectionCtx<'info> {
+    #[account(mut)]
+    pub raiser: Signer<'info>,
+    pub inspection: Account<'info, Inspection>,
+    #[account(
+        constraint = inspection_request.inspection_pda == inspection.key() @ SupplyChainErrors::InvalidInspectionRequest,
+    )]
+    pub inspection_request: Account<'info, InspectionRequest>,
+    #[account(address = inspection.product_pda)]
+    pub product: Account<'info, Product>,
+    #[account(mut, address = inspection.inspector_pda @ SupplyChainErrors::InvalidInspectorId)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(mut, seeds = [b"inspector_stake", inspector.key().as_ref()], bump)]
+    pub stake: Account<'info, InspectorStake>,
+    pub customer_product: Option<Account<'info, CustomerProduct>>,
+    #[account(
+        init,
+        payer = raiser,
+        space = ANCHOR_DISCRIMINATOR_SIZE + InspectionDispute::INIT_SPACE,
+        seeds = [b"inspection_dispute", inspection.key().as_ref()],
+        bump,
+    )]
+    pub dispute: Account<'info, InspectionDispute>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AssignDisputeReviewerCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(mut)]
+    pub dispute: Account<'info, InspectionDispute>,
+    #[account(constraint = !reviewer.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub reviewer: Account<'info, InspectorProfile>,
+    #[account(
+        seeds = [b"inspector_stake", reviewer.key().as_ref()],
+        bump,
+        constraint = reviewer_stake.amount >= MIN_INSPECTOR_STAKE @ SupplyChainErrors::InsufficientInspectorStake,
+    )]
+    pub reviewer_stake: Account<'info, InspectorStake>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolveInspectionDisputeCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"user", owner.key().as_ref()], bump)]
+    pub user: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [b"inspector_profile", owner.key().as_ref()],
+        bump,
+        constraint = reviewer.key() == dispute.reviewer_pda @ SupplyChainErrors::UnauthorizedAccess,
+    )]
+    pub reviewer: Account<'info, InspectorProfile>,
+    #[account(mut, seeds = [b"inspector_stake", reviewer.key().as_ref()], bump)]
+    pub reviewer_stake: Account<'info, InspectorStake>,
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Inspection::INIT_SPACE,
+        seeds = [b"inspection", reviewer.key().as_ref(), (reviewer.inspection_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub review_inspection: Account<'info, Inspection>,
+    #[account(mut, seeds = [b"inspection_dispute", dispute.inspection_pda.as_ref()], bump)]
+    pub dispute: Account<'info, InspectionDispute>,
+    #[account(address = dispute.inspection_pda)]
+    pub original_inspection: Account<'info, Inspection>,
+    #[account(mut, address = dispute.product_pda)]
+    pub product: Account<'info, Product>,
+    #[account(mut, address = dispute.inspector_pda @ SupplyChainErrors::InvalidInspectorId)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(mut, seeds = [b"inspector_stake", inspector.key().as_ref()], bump)]
+    pub stake: Account<'info, InspectorStake>,
+    /// CHECK: Factory owner that paid the inspection fee, only receives the refund
+    #[account(mut, address = dispute.refund_to)]
+    pub refund_to: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelInspectionDisputeCtx<'info> {
+    #[account(mut, address = dispute.raised_by @ SupplyChainErrors::UnauthorizedAccess)]
+    pub raiser: Signer<'info>,
+    #[account(mut, seeds = [b"inspection_dispute", dispute.inspection_pda.as_ref()], bump)]
+    pub dispute: Account<'info, InspectionDispute>,
+    #[account(mut, address = dispute.inspector_pda @ SupplyChainErrors::InvalidInspectorId)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(mut, seeds = [b"inspector_stake", inspector.key().as_ref()], bump)]
+    pub stake: Account<'info, InspectorStake>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExpireInspectionDisputeCtx<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut, seeds = [b"inspection_dispute", dispute.inspection_pda.as_ref()], bump)]
+    pub dispute: Account<'info, InspectionDispute>,
+    #[account(mut, address = dispute.inspector_pda @ SupplyChainErrors::InvalidInspectorId)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(mut, seeds = [b"inspector_stake", inspector.key().as_ref()], bump)]
+    pub stake: Account<'info, InspectorStake>,
+    /// CHECK: Wallet that raised the dispute, only receives the unused review fee
+    #[account(mut, address = dispute.raised_by)]
+    pub raised_by: UncheckedAccount<'info>,
+}
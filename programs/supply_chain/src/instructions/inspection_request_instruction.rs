@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    constants::{ANCHOR_DISCRIMINATOR_SIZE, MIN_INSPECTOR_STAKE},
+    errors::SupplyChainErrors,
+    events::{InspectionRequestAccepted, InspectionRequestCancelled, InspectionRequested},
+    states::{
+        factory::Factory,
+        inspection_request::{InspectionRequest, InspectionRequestStatus},
+        inspector_profile::InspectorProfile,
+        inspector_stake::InspectorStake,
+        product::Product,
+        program_state::ProgramState,
+    },
+};
+
+// The factory picks the inspector and escrows the agreed fee in the request account,
+// the fee is released to the inspector when the inspection or re-inspection is submitted
+pub fn request_inspection(ctx: Context<RequestInspectionCtx>, fee: u64) -> Result<()> {
+    let request = &mut ctx.accounts.inspection_request;
+    let product = &mut ctx.accounts.product;
+    let inspector = &ctx.accounts.inspector;
+    let requester = &ctx.accounts.requester;
+    require!(
+        ctx.accounts.factory.owner == requester.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(fee > 0, SupplyChainErrors::InvalidInspectionFee);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: requester.to_account_info(),
+                to: request.to_account_info(),
+            },
+        ),
+        fee,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    request.request_id = product
+        .inspection_request_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    request.product_pda = product.key();
+    request.factory_pda = product.factory_pda;
+    request.requested_by = requester.key();
+    request.inspector_pda = inspector.key();
+    request.fee = fee;
+    request.status = InspectionRequestStatus::Requested;
+    request.created_at = now;
+    product.inspection_request_count = request.request_id;
+
+    emit_cpi!(InspectionRequested {
+        request: request.key(),
+        product: product.key(),
+        inspector: inspector.key(),
+        requested_by: requester.key(),
+        fee,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn accept_inspection_request(ctx: Context<AcceptInspectionRequestCtx>) -> Result<()> {
+    let request = &mut ctx.accounts.inspection_request;
+    request.transition_to(InspectionRequestStatus::Accepted)?;
+    request.accepted_at = Clock::get()?.unix_timestamp as u64;
+
+    emit_cpi!(InspectionRequestAccepted {
+        request: request.key(),
+        inspector: ctx.accounts.inspector.key(),
+        timestamp: request.accepted_at,
+    });
+    Ok(())
+}
+
+// Only requests the inspector has not accepted yet can be withdrawn by the factory
+pub fn cancel_inspection_request(ctx: Context<CancelInspectionRequestCtx>) -> Result<()> {
+    let request = &mut ctx.accounts.inspection_request;
+    request.transition_to(InspectionRequestStatus::Cancelled)?;
+
+    let refunded = request.fee;
+    **request.to_account_info().try_borrow_mut_lamports()? -= refunded;
+    **ctx
+        .accounts
+        .requester
+        .to_account_info()
+        .try_borrow_mut_lamports()? += refunded;
+
+    emit_cpi!(InspectionRequestCancelled {
+        request: request.key(),
+        refunded,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestInspectionCtx<'info> {
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    #[account(
+        init,
+        payer = requester,
+        space = ANCHOR_DISCRIMINATOR_SIZE + InspectionRequest::INIT_SPACE,
+        seeds = [b"inspection_request", product.key().as_ref(), (product.inspection_request_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub inspection_request: Account<'info, InspectionRequest>,
+    #[account(
+        mut,
+        constraint = !product.recalled @ SupplyChainErrors::ProductRecalled,
+    )]
+    pub product: Account<'info, Product>,
+    #[account(address = product.factory_pda @ SupplyChainErrors::InvalidFactory)]
+    pub factory: Account<'info, Factory>,
+    #[account(constraint = !inspector.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptInspectionRequestCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"inspector_profile", owner.key().as_ref()], bump)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(
+        seeds = [b"inspector_stake", inspector.key().as_ref()],
+        bump,
+        constraint = stake.amount >= MIN_INSPECTOR_STAKE @ SupplyChainErrors::InsufficientInspectorStake,
+    )]
+    pub stake: Account<'info, InspectorStake>,
+    #[account(
+        mut,
+        constraint = inspection_request.inspector_pda == inspector.key() @ SupplyChainErrors::InvalidInspectionRequest,
+    )]
+    pub inspection_request: Account<'info, InspectionRequest>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelInspectionRequestCtx<'info> {
+    #[account(mut)]
+    pub requester: Signer<'info>,
+    #[account(
+        mut,
+        constraint = inspection_request.requested_by == requester.key() @ SupplyChainErrors::UnauthorizedAccess,
+    )]
+    pub inspection_request: Account<'info, InspectionRequest>,
+}
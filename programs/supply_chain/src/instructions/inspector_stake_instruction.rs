@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    constants::{ANCHOR_DISCRIMINATOR_SIZE, MIN_INSPECTOR_STAKE},
+    errors::SupplyChainErrors,
+    events::{InspectorSlashed, InspectorStaked, InspectorUnstaked},
+    states::{
+        inspector_profile::InspectorProfile, inspector_stake::InspectorStake, product::Product,
+        program_state::ProgramState, recall_fund::RecallFund,
+    },
+};
+
+pub fn stake_inspector(ctx: Context<StakeInspectorCtx>, amount: u64) -> Result<()> {
+    let stake = &mut ctx.accounts.stake;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if stake.created_at == 0 {
+        stake.inspector_pda = ctx.accounts.inspector.key();
+        stake.owner = ctx.accounts.owner.key();
+        stake.created_at = now;
+    }
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: stake.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    stake.amount = stake
+        .amount
+        .checked_add(amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(InspectorStaked {
+        inspector: stake.inspector_pda,
+        amount,
+        total: stake.amount,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// Stake above the minimum is always free, the rest stays locked while inspections are live
+pub fn unstake_inspector(ctx: Context<UnstakeInspectorCtx>, amount: u64) -> Result<()> {
+    let stake = &mut ctx.accounts.stake;
+    let now = Clock::get()?.unix_timestamp as u64;
+    require!(
+        amount <= stake.amount,
+        SupplyChainErrors::InsufficientBalance
+    );
+    let remaining = stake.amount - amount;
+    require!(
+        remaining >= MIN_INSPECTOR_STAKE || now >= stake.locked_until,
+        SupplyChainErrors::StakeLocked
+    );
+
+    **stake.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **ctx
+        .accounts
+        .owner
+        .to_account_info()
+        .try_borrow_mut_lamports()? += amount;
+    stake.amount = remaining;
+
+    emit_cpi!(InspectorUnstaked {
+        inspector: stake.inspector_pda,
+        amount,
+        total: stake.amount,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// The platform owner slashes the inspector that passed a recalled product, the slashed
+// lamports top up the product's recall fund so its holders can claim them
+pub fn slash_inspector_stake(ctx: Context<SlashInspectorStakeCtx>, amount: u64) -> Result<()> {
+    require!(
+        ctx.accounts.program_state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let stake = &mut ctx.accounts.stake;
+    let recall_fund = &mut ctx.accounts.recall_fund;
+    require!(
+        amount <= stake.amount,
+        SupplyChainErrors::InsufficientBalance
+    );
+
+    **stake.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **recall_fund.to_account_info().try_borrow_mut_lamports()? += amount;
+    stake.amount -= amount;
+    stake.slashed = stake
+        .slashed
+        .checked_add(amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    recall_fund.funded = recall_fund
+        .funded
+        .checked_add(amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(InspectorSlashed {
+        inspector: stake.inspector_pda,
+        product: ctx.accounts.product.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeInspectorCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"inspector_profile", owner.key().as_ref()], bump)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + InspectorStake::INIT_SPACE,
+        seeds = [b"inspector_stake", inspector.key().as_ref()],
+        bump,
+    )]
+    pub stake: Account<'info, InspectorStake>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnstakeInspectorCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"inspector_profile", owner.key().as_ref()], bump)]
+    pub inspector: Account<'info, InspectorProfile>,
+    #[account(mut, seeds = [b"inspector_stake", inspector.key().as_ref()], bump)]
+    pub stake: Account<'info, InspectorStake>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SlashInspectorStakeCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        constraint = product.recalled @ SupplyChainErrors::ProductNotRecalled,
+        constraint = product.quality_checked @ SupplyChainErrors::ProductNotQualityChecked,
+    )]
+    pub product: Account<'info, Product>,
+    #[account(mut, seeds = [b"inspector_stake", product.inspector_pda.as_ref()], bump)]
+    pub stake: Account<'info, InspectorStake>,
+    #[account(mut, seeds = [b"recall_fund", product.key().as_ref()], bump)]
+    pub recall_fund: Account<'info, RecallFund>,
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::events::UserRolesChanged;
+use crate::states::{
+    program_state::ProgramState,
+    user::{Role, User},
+};
+
+pub fn add_user_role(ctx: Context<UpdateUserRoleCtx>, role: Role) -> Result<()> {
+    let user = &mut ctx.accounts.user;
+    require!(
+        user.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(!user.has_role(role), SupplyChainErrors::RoleAlreadyAssigned);
+    if role.is_privileged() {
+        require!(
+            ctx.accounts
+                .authority
+                .as_ref()
+                .is_some_and(|authority| authority.key() == ctx.accounts.program_state.owner),
+            SupplyChainErrors::UnauthorizedAccess
+        );
+    }
+    user.add_role(role);
+
+    emit_cpi!(UserRolesChanged {
+        user: user.key(),
+        roles: user.roles,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+pub fn remove_user_role(ctx: Context<UpdateUserRoleCtx>, role: Role) -> Result<()> {
+    let user = &mut ctx.accounts.user;
+    require!(
+        user.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(user.has_role(role), SupplyChainErrors::RoleNotAssigned);
+    user.remove_role(role);
+    // A user must always keep at least one role
+    require!(user.roles != 0, SupplyChainErrors::InvalidRole);
+
+    emit_cpi!(UserRolesChanged {
+        user: user.key(),
+        roles: user.roles,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateUserRoleCtx<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump,
+    )]
+    pub user: Account<'info, User>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    // Platform owner co-signature, only required to add a privileged role
+    pub authority: Option<Signer<'info>>,
+}
@@ -4,37 +4,83 @@ pub mod change_platform_fee;
 pub use change_platform_fee::*;
 pub mod create_user_instruction;
 pub use create_user_instruction::*;
+pub mod manage_user_roles_instruction;
+pub use manage_user_roles_instruction::*;
 pub mod create_factory_instruction;
 pub use create_factory_instruction::*;
 pub mod create_product_instruction;
 pub use create_product_instruction::*;
+pub mod register_inspector_instruction;
+pub use register_inspector_instruction::*;
+pub mod inspector_stake_instruction;
+pub use inspector_stake_instruction::*;
+pub mod inspection_dispute_instruction;
+pub use inspection_dispute_instruction::*;
+pub mod inspection_request_instruction;
+pub use inspection_request_instruction::*;
 pub mod inspect_product_instruction;
 pub use inspect_product_instruction::*;
-pub mod pay_product_inspector_instruction;
-pub use pay_product_inspector_instruction::*;
 pub mod creare_warehouse_instruction;
 pub use creare_warehouse_instruction::*;
-pub mod withdraw_balance_as_inspector_instruction;
-pub use withdraw_balance_as_inspector_instruction::*;
-pub mod withdraw_balance_as_factory;
-pub use withdraw_balance_as_factory::*;
+pub mod resize_warehouse_instruction;
+pub use resize_warehouse_instruction::*;
 pub mod create_seller;
 pub use create_seller::*;
-pub mod buy_product_as_warehouse;
-pub use buy_product_as_warehouse::*;
-pub mod withdraw_balance_as_warehouse_instruction;
-pub use withdraw_balance_as_warehouse_instruction::*;
+pub mod place_purchase_order_instruction;
+pub use place_purchase_order_instruction::*;
+pub mod review_purchase_order_instruction;
+pub use review_purchase_order_instruction::*;
+pub mod dispatch_purchase_order_instruction;
+pub use dispatch_purchase_order_instruction::*;
+pub mod receive_purchase_order_instruction;
+pub use receive_purchase_order_instruction::*;
+pub mod transfer_warehouse_stock_instruction;
+pub use transfer_warehouse_stock_instruction::*;
 pub mod create_order_as_seller_instruction;
 pub use create_order_as_seller_instruction::*;
+pub mod cancel_order_as_seller_instruction;
+pub use cancel_order_as_seller_instruction::*;
+pub mod review_order_as_warehouse_instruction;
+pub use review_order_as_warehouse_instruction::*;
+pub mod dispute_order_as_seller_instruction;
+pub use dispute_order_as_seller_instruction::*;
+pub mod refund_order_instruction;
+pub use refund_order_instruction::*;
 pub mod create_logistics_instruction;
 pub use create_logistics_instruction::*;
 pub mod send_logistics_to_seller;
 pub use send_logistics_to_seller::*;
+pub mod record_checkpoint_instruction;
+pub use record_checkpoint_instruction::*;
+pub mod handoff_custody_instruction;
+pub use handoff_custody_instruction::*;
 pub mod receive_product_instruction_as_seller;
 pub use receive_product_instruction_as_seller::*;
-pub mod withdraw_balance_as_seller_instruction;
-pub use withdraw_balance_as_seller_instruction::*;
-pub mod withdraw_balance_as_logistic_instruction;
-pub use withdraw_balance_as_logistic_instruction::*;
 pub mod buy_product_as_customer;
-pub use buy_product_as_customer::*;
\ No newline at end of file
+pub use buy_product_as_customer::*;
+pub mod token_vault_instruction;
+pub use token_vault_instruction::*;
+pub mod withdraw_token_balance_instruction;
+pub use withdraw_token_balance_instruction::*;
+pub mod withdraw_instruction;
+pub use withdraw_instruction::*;
+pub mod change_min_withdraw_amount;
+pub use change_min_withdraw_amount::*;
+pub mod fee_exemption_instruction;
+pub use fee_exemption_instruction::*;
+pub mod ownership_instruction;
+pub use ownership_instruction::*;
+pub mod treasury_instruction;
+pub use treasury_instruction::*;
+pub mod pause_instruction;
+pub use pause_instruction::*;
+pub mod restock_product_instruction;
+pub use restock_product_instruction::*;
+pub mod inspect_batch_instruction;
+pub use inspect_batch_instruction::*;
+pub mod update_product_instruction;
+pub use update_product_instruction::*;
+pub mod recall_product_instruction;
+pub use recall_product_instruction::*;
+pub mod claim_recall_refund_instruction;
+pub use claim_recall_refund_instruction::*;
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::events::{OwnershipTransferProposed, OwnershipTransferred};
+use crate::states::program_state::ProgramState;
+
+// Ownership moves in two steps so a typo in the new key cannot lock the platform out,
+// proposing Pubkey::default() cancels a pending transfer
+pub fn propose_owner(ctx: Context<ProposeOwnerCtx>, new_owner: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.program_state;
+    require!(
+        state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    state.pending_owner = new_owner;
+
+    emit_cpi!(OwnershipTransferProposed {
+        owner: state.owner,
+        pending_owner: new_owner,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+pub fn accept_ownership(ctx: Context<AcceptOwnershipCtx>) -> Result<()> {
+    let state = &mut ctx.accounts.program_state;
+    require!(
+        state.pending_owner != Pubkey::default(),
+        SupplyChainErrors::NoPendingOwner
+    );
+    require!(
+        state.pending_owner == ctx.accounts.new_owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let old_owner = state.owner;
+    state.owner = state.pending_owner;
+    state.pending_owner = Pubkey::default();
+
+    emit_cpi!(OwnershipTransferred {
+        old_owner,
+        new_owner: state.owner,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeOwnerCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptOwnershipCtx<'info> {
+    pub new_owner: Signer<'info>,
+    #[account(mut, seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::events::{AccountFreezeChanged, ProgramPauseChanged};
+use crate::states::{balance_holder::BalanceHolderKind, program_state::ProgramState};
+
+pub fn set_program_paused(ctx: Context<SetProgramPausedCtx>, paused: bool) -> Result<()> {
+    let state = &mut ctx.accounts.program_state;
+    require!(
+        state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    state.paused = paused;
+
+    emit_cpi!(ProgramPauseChanged {
+        paused,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+pub fn set_account_frozen(
+    ctx: Context<SetAccountFrozenCtx>,
+    kind: BalanceHolderKind,
+    frozen: bool,
+) -> Result<()> {
+    require!(
+        ctx.accounts.program_state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    kind.set_frozen(&ctx.accounts.holder.to_account_info(), frozen)?;
+
+    emit_cpi!(AccountFreezeChanged {
+        account: ctx.accounts.holder.key(),
+        kind,
+        frozen,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetProgramPausedCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAccountFrozenCtx<'info> {
+    pub owner: Signer<'info>,
+    /// CHECK: Deserialized according to the balance holder kind in the handler
+    #[account(mut, owner = crate::ID)]
+    pub holder: UncheckedAccount<'info>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::{ANCHOR_DISCRIMINATOR_SIZE, ORDER_SHIPMENT_DEADLINE};
+use crate::errors::SupplyChainErrors::*;
+use crate::events::PurchaseOrderPlaced;
+use crate::instructions::token_vault_instruction::transfer_tokens;
+use crate::states::factory::Factory;
+use crate::states::{
+    batch::Batch,
+    inspection::Inspection,
+    product::Product,
+    program_state::ProgramState,
+    purchase_order::{PurchaseOrder, PurchaseOrderStatus},
+    transaction::Transaction,
+    user::{Role, User},
+    warehouse::Warehouse,
+};
+
+pub fn place_purchase_order(
+    ctx: Context<PlacePurchaseOrderCtx>,
+    product_id: u64,
+    factory_id: u64,
+    quantity: u64,
+) -> Result<()> {
+    let transaction = &mut ctx.accounts.transaction;
+    let user = &mut ctx.accounts.user;
+    let warehouse = &mut ctx.accounts.warehouse;
+    let product = &mut ctx.accounts.product;
+    let factory = &ctx.accounts.factory;
+    let purchase_order = &mut ctx.accounts.purchase_order;
+    let payer = &ctx.accounts.warehouse_owner;
+    if !user.has_role(Role::Warehouse) {
+        return Err(UnauthorizedAccess.into());
+    }
+    if warehouse.owner != payer.key() {
+        return Err(UnauthorizedAccess.into());
+    }
+    if product.product_id != product_id {
+        return Err(InvalidProductId.into());
+    }
+    if factory.factory_id != factory_id || product.factory_pda != factory.key() {
+        return Err(InvalidFactory.into());
+    }
+    if quantity == 0 || product.product_stock < quantity {
+        return Err(InsufficientStock.into());
+    }
+    let now = Clock::get()?.unix_timestamp as u64;
+    if !ctx.accounts.inspection.is_valid_at(now) {
+        return Err(InspectionNotValid.into());
+    }
+    // Restocked units are sold from their own batch, so expired ones stay at the factory
+    let batch_pda = match ctx.accounts.batch.as_mut() {
+        Some(batch) => {
+            if batch.product_pda != product.key() || !batch.passed {
+                return Err(BatchNotReleased.into());
+            }
+            if now >= batch.expires_at {
+                return Err(BatchExpired.into());
+            }
+            if batch.remaining < quantity {
+                return Err(InsufficientStock.into());
+            }
+            batch.remaining -= quantity;
+            product.batch_stock -= quantity;
+            batch.key()
+        }
+        None => {
+            if product.base_stock() < quantity {
+                return Err(InsufficientStock.into());
+            }
+            Pubkey::default()
+        }
+    };
+
+    // Space is set aside now so the delivery is guaranteed to fit
+    let inbound_volume = product.unit_volume.checked_mul(quantity).ok_or(Overflow)?;
+    warehouse.occupy(inbound_volume)?;
+    // Units are held back from other buyers while the order is open
+    product.product_stock -= quantity;
+
+    let total_amount_to_pay = product
+        .product_price
+        .checked_mul(quantity)
+        .ok_or(Overflow)?;
+    // Payment is held in the purchase order, or its token vault, until the warehouse confirms
+    // receipt
+    let (payment_mint, payment_destination) = match ctx.accounts.payment_mint.as_ref() {
+        None => {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: payer.to_account_info(),
+                        to: purchase_order.to_account_info(),
+                    },
+                ),
+                total_amount_to_pay,
+            )?;
+            (Pubkey::default(), purchase_order.key())
+        }
+        Some(payment_mint) => {
+            let (Some(payer_token_account), Some(escrow_vault), Some(token_program)) = (
+                ctx.accounts.payer_token_account.as_ref(),
+                ctx.accounts.escrow_vault.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) else {
+                return Err(InvalidTokenVault.into());
+            };
+            transfer_tokens(
+                payer_token_account,
+                escrow_vault,
+                payment_mint,
+                payer,
+                token_program,
+                total_amount_to_pay,
+            )?;
+            (payment_mint.key(), escrow_vault.key())
+        }
+    };
+
+    purchase_order.purchase_order_id = warehouse
+        .purchase_order_count
+        .checked_add(1)
+        .ok_or(Overflow)?;
+    purchase_order.warehouse_pda = warehouse.key();
+    purchase_order.factory_pda = factory.key();
+    purchase_order.product_pda = product.key();
+    purchase_order.quantity = quantity;
+    purchase_order.total_price = total_amount_to_pay;
+    purchase_order.payment_mint = payment_mint;
+    purchase_order.batch_pda = batch_pda;
+    purchase_order.ship_by = now.checked_add(ORDER_SHIPMENT_DEADLINE).ok_or(Overflow)?;
+    purchase_order.status = PurchaseOrderStatus::Placed;
+    purchase_order.created_at = now;
+    purchase_order.updated_at = now;
+    warehouse.purchase_order_count = purchase_order.purchase_order_id;
+
+    transaction.transaction_id = user.transaction_count + 1;
+    transaction.from = payer.key();
+    transaction.to = payment_destination;
+    transaction.amount = total_amount_to_pay;
+    transaction.timestamp = now;
+    transaction.status = true;
+
+    user.transaction_count += 1;
+
+    emit_cpi!(PurchaseOrderPlaced {
+        purchase_order: purchase_order.key(),
+        warehouse: warehouse.key(),
+        factory: factory.key(),
+        product: product.key(),
+        quantity,
+        total_price: total_amount_to_pay,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PlacePurchaseOrderCtx<'info> {
+    #[account(
+        init,
+        payer = warehouse_owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + PurchaseOrder::INIT_SPACE,
+        seeds = [b"purchase_order", warehouse.key().as_ref(), (warehouse.purchase_order_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub purchase_order: Box<Account<'info, PurchaseOrder>>,
+    #[account(
+        init,
+        payer = warehouse_owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [b"transaction", user.key().as_ref(), (user.transaction_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub transaction: Box<Account<'info, Transaction>>,
+    #[account(mut)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, constraint = !warehouse.frozen @ AccountFrozen)]
+    pub warehouse: Box<Account<'info, Warehouse>>,
+    #[account(mut, constraint = !product.recalled @ ProductRecalled)]
+    pub product: Box<Account<'info, Product>>,
+    #[account(
+        seeds = [b"inspection", product.inspector_pda.as_ref(), product.inspection_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub inspection: Box<Account<'info, Inspection>>,
+    #[account(constraint = !factory.frozen @ AccountFrozen)]
+    pub factory: Box<Account<'info, Factory>>,
+    // Only required when buying units of a restocked batch
+    #[account(mut)]
+    pub batch: Option<Box<Account<'info, Batch>>>,
+    #[account(seeds = [b"program_state"], bump, constraint = !program_state.paused @ ProgramPaused)]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(mut)]
+    pub warehouse_owner: Signer<'info>,
+    // Token accounts below are only required when paying with the payment mint
+    #[account(
+        init,
+        payer = warehouse_owner,
+        seeds = [b"vault", purchase_order.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = vault_authority,
+    )]
+    pub escrow_vault: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = warehouse_owner,
+    )]
+    pub payer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(address = program_state.payment_mint @ InvalidPaymentMint)]
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    errors::SupplyChainErrors,
+    events::{ProductRecalled, RecallFunded},
+    states::{
+        factory::Factory, inspector_profile::InspectorProfile, product::Product,
+        recall_fund::RecallFund,
+    },
+};
+
+// Either the factory owner or the inspector that certified the product can recall it
+pub fn recall_product(ctx: Context<RecallProductCtx>) -> Result<()> {
+    let product = &mut ctx.accounts.product;
+    let recall_fund = &mut ctx.accounts.recall_fund;
+    let signer = ctx.accounts.signer.key();
+    let by_factory = ctx
+        .accounts
+        .factory
+        .as_ref()
+        .is_some_and(|factory| factory.owner == signer);
+    let by_inspector = ctx
+        .accounts
+        .inspector
+        .as_ref()
+        .is_some_and(|inspector| inspector.owner == signer);
+    require!(
+        by_factory || by_inspector,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(!product.recalled, SupplyChainErrors::ProductRecalled);
+    // A recall counts against the inspector that let the product pass
+    if product.quality_checked {
+        let inspector = ctx
+            .accounts
+            .inspector
+            .as_mut()
+            .ok_or(SupplyChainErrors::InvalidInspectorId)?;
+        inspector.recalls_on_passed = inspector
+            .recalls_on_passed
+            .checked_add(1)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        inspector.refresh_reputation();
+    }
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    product.recalled = true;
+    product.recalled_at = now;
+    recall_fund.product_pda = product.key();
+    recall_fund.recalled_by = signer;
+    recall_fund.created_at = now;
+
+    emit_cpi!(ProductRecalled {
+        product: product.key(),
+        recalled_by: signer,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn fund_recall(ctx: Context<FundRecallCtx>, amount: u64) -> Result<()> {
+    let recall_fund = &mut ctx.accounts.recall_fund;
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: recall_fund.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    recall_fund.funded = recall_fund
+        .funded
+        .checked_add(amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(RecallFunded {
+        product: recall_fund.product_pda,
+        funder: ctx.accounts.funder.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecallProductCtx<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub product: Account<'info, Product>,
+    #[account(address = product.factory_pda @ SupplyChainErrors::InvalidFactory)]
+    pub factory: Option<Account<'info, Factory>>,
+    // The inspector that certified the product, required once it passed an inspection
+    #[account(mut, address = product.inspector_pda @ SupplyChainErrors::InvalidInspectorId)]
+    pub inspector: Option<Account<'info, InspectorProfile>>,
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + RecallFund::INIT_SPACE,
+        seeds = [b"recall_fund", product.key().as_ref()],
+        bump,
+    )]
+    pub recall_fund: Account<'info, RecallFund>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundRecallCtx<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"recall_fund", recall_fund.product_pda.as_ref()],
+        bump,
+    )]
+    pub recall_fund: Account<'info, RecallFund>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,44 +1,188 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
 use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
 use crate::errors::SupplyChainErrors;
-use crate::states::order::Order;
+use crate::events::{OrderDelivered, PlatformFeeCollected};
+use crate::instructions::token_vault_instruction::transfer_from_vault;
+use crate::states::order::{Order, OrderStatus};
 use crate::states::{
-    logistics::Logistics, seller::Seller, seller_product_stock::SellerProductStock, user::User,
+    balance_holder::BalanceHolderKind,
+    fee_exemption::FeeExemption,
+    logistics_provider::LogisticsProvider,
+    order_escrow::OrderEscrow,
+    program_state::ProgramState,
+    seller::Seller,
+    seller_product_stock::SellerProductStock,
+    shipment::{Shipment, ShipmentStatus},
+    treasury::Treasury,
+    user::{Role, User},
+    warehouse::Warehouse,
 };
 
 pub fn receive_product_instruction_as_seller(
     ctx: Context<ReceiveProductAsSellerCtx>,
 ) -> Result<()> {
     let logistics = &mut ctx.accounts.logistics;
+    let shipment = &mut ctx.accounts.shipment;
     let user = &mut ctx.accounts.user;
     let seller = &mut ctx.accounts.seller;
     let seller_product_stock = &mut ctx.accounts.seller_product_stock;
     let order = &mut ctx.accounts.order;
+    let escrow = &mut ctx.accounts.escrow;
+    let warehouse = &mut ctx.accounts.warehouse;
 
-    require!(user.role == "SELLER", SupplyChainErrors::UnauthorizedAccess);
+    require!(user.has_role(Role::Seller), SupplyChainErrors::UnauthorizedAccess);
     require!(
         user.owner.key() == seller.owner.key(),
         SupplyChainErrors::UnauthorizedAccess
     );
+    let signer = ctx.accounts.signer.key();
+    match order.status {
+        // Disputes are settled in the warehouse's favour by the platform owner
+        OrderStatus::Disputed => require!(
+            signer == ctx.accounts.program_state.owner,
+            SupplyChainErrors::UnauthorizedAccess
+        ),
+        _ => require!(
+            signer == user.owner.key(),
+            SupplyChainErrors::UnauthorizedAccess
+        ),
+    }
+    require!(
+        order.seller_pda == seller.key(),
+        SupplyChainErrors::InvalidOrder
+    );
+    require!(
+        !escrow.released && !escrow.refunded,
+        SupplyChainErrors::EscrowAlreadySettled
+    );
+
+    require!(
+        shipment.status == ShipmentStatus::InTransit,
+        SupplyChainErrors::ShipmentNotInTransit
+    );
+    // The carrier must have handed the goods to the seller before payment is released
+    require!(
+        shipment.custodian == seller.owner,
+        SupplyChainErrors::InvalidCustodian
+    );
+
+    order.transition_to(OrderStatus::Delivered)?;
+
+    // Release the escrowed payment to the warehouse now that the goods arrived
+    let amount = escrow.amount;
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        ctx.accounts
+            .program_state
+            .purchase_fee_for(BalanceHolderKind::Warehouse, amount)?
+    };
+    let warehouse_amount = amount - platform_fee;
+    if escrow.payment_mint == Pubkey::default() {
+        **escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **warehouse.to_account_info().try_borrow_mut_lamports()? += warehouse_amount;
+        **ctx
+            .accounts
+            .treasury
+            .to_account_info()
+            .try_borrow_mut_lamports()? += platform_fee;
+        warehouse.balance = warehouse
+            .balance
+            .checked_add(warehouse_amount)
+            .ok_or(SupplyChainErrors::Overflow)?;
+    } else {
+        let (
+            Some(escrow_vault),
+            Some(warehouse_vault),
+            Some(treasury_vault),
+            Some(vault_authority),
+            Some(vault_authority_bump),
+            Some(payment_mint),
+            Some(token_program),
+        ) = (
+            ctx.accounts.escrow_vault.as_ref(),
+            ctx.accounts.warehouse_vault.as_ref(),
+            ctx.accounts.treasury_vault.as_ref(),
+            ctx.accounts.vault_authority.as_ref(),
+            ctx.bumps.vault_authority,
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )
+        else {
+            return Err(SupplyChainErrors::InvalidTokenVault.into());
+        };
+        require_keys_eq!(
+            payment_mint.key(),
+            escrow.payment_mint,
+            SupplyChainErrors::InvalidPaymentMint
+        );
+        transfer_from_vault(
+            escrow_vault,
+            warehouse_vault,
+            payment_mint,
+            vault_authority,
+            vault_authority_bump,
+            token_program,
+            warehouse_amount,
+        )?;
+        if platform_fee > 0 {
+            transfer_from_vault(
+                escrow_vault,
+                treasury_vault,
+                payment_mint,
+                vault_authority,
+                vault_authority_bump,
+                token_program,
+                platform_fee,
+            )?;
+        }
+    }
+    escrow.amount = 0;
+    escrow.released = true;
 
+    let now = Clock::get()?.unix_timestamp as u64;
     seller.products_count += 1;
     seller_product_stock.seller_id = seller.seller_id;
-    seller_product_stock.stock_quantity = logistics.product_stock;
-    seller_product_stock.product_id = logistics.product_id;
-    seller_product_stock.created_at = Clock::get()?.unix_timestamp as u64;
+    seller_product_stock.stock_quantity = shipment.product_stock;
+    seller_product_stock.product_id = shipment.product_id;
+    seller_product_stock.created_at = now;
     seller_product_stock.seller_pda = seller.key();
-    seller_product_stock.product_pda = order.product_pda.key();
+    seller_product_stock.product_pda = shipment.product_pda;
+    seller_product_stock.unit_price = order
+        .total_price
+        .checked_div(order.product_stock)
+        .ok_or(SupplyChainErrors::Overflow)?;
 
-    logistics.delivered = true;
-    logistics.status = String::from("DELIVERED");
-    order.status = String::from("DELIVERED");
-    logistics.shipment_ended_at = Clock::get()?.unix_timestamp as u64;
+    shipment.status = ShipmentStatus::Delivered;
+    shipment.shipment_ended_at = now;
+    logistics.active_shipments = logistics.active_shipments.saturating_sub(1);
+
+    if platform_fee > 0 {
+        emit_cpi!(PlatformFeeCollected {
+            payer: escrow.key(),
+            payee: warehouse.key(),
+            amount: platform_fee,
+            timestamp: now,
+        });
+    }
+    emit_cpi!(OrderDelivered {
+        order: order.key(),
+        shipment: shipment.key(),
+        seller: seller.key(),
+        warehouse: warehouse.key(),
+        quantity: shipment.product_stock,
+        released_amount: amount,
+        timestamp: now,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ReceiveProductAsSellerCtx<'info> {
+    // The seller, or the platform owner when settling a dispute
     #[account(mut)]
     pub signer: Signer<'info>,
     #[account(
@@ -51,11 +195,52 @@ pub struct ReceiveProductAsSellerCtx<'info> {
     pub seller_product_stock: Account<'info, SellerProductStock>,
     #[account(mut)]
     pub user: Account<'info, User>,
-    #[account(mut)]
+    #[account(mut, constraint = !seller.frozen @ SupplyChainErrors::AccountFrozen)]
     pub seller: Account<'info, Seller>,
     #[account(mut)]
     pub order: Account<'info, Order>,
-    #[account(mut)]
-    pub logistics: Account<'info, Logistics>,
+    #[account(
+        mut,
+        seeds = [b"shipment", order.key().as_ref()],
+        bump,
+    )]
+    pub shipment: Account<'info, Shipment>,
+    #[account(mut, address = shipment.provider_pda)]
+    pub logistics: Account<'info, LogisticsProvider>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, OrderEscrow>,
+    #[account(
+        mut,
+        address = order.warehouse_pda,
+        constraint = !warehouse.frozen @ SupplyChainErrors::AccountFrozen,
+    )]
+    pub warehouse: Account<'info, Warehouse>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", warehouse.owner.as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
+    // Token accounts below are only required when the order was paid with the payment mint
+    #[account(mut, seeds = [b"vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, seeds = [b"vault", warehouse.key().as_ref()], bump)]
+    pub warehouse_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, seeds = [b"vault", treasury.key().as_ref()], bump)]
+    pub treasury_vault: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::errors::SupplyChainErrors;
+use crate::events::{PlatformFeeCollected, PurchaseOrderStatusChanged, WarehousePurchase};
+use crate::instructions::token_vault_instruction::transfer_from_vault;
+use crate::states::{
+    balance_holder::BalanceHolderKind,
+    factory::Factory,
+    fee_exemption::FeeExemption,
+    logistics_provider::LogisticsProvider,
+    product::Product,
+    program_state::ProgramState,
+    purchase_order::{PurchaseOrder, PurchaseOrderStatus},
+    shipment::{Shipment, ShipmentStatus},
+    treasury::Treasury,
+    user::{Role, User},
+    warehouse::Warehouse,
+    warehouse_stock::WarehouseStock,
+};
+
+// The warehouse confirms delivery, which releases the escrowed payment to the factory
+pub fn receive_purchase_order(ctx: Context<ReceivePurchaseOrderCtx>) -> Result<()> {
+    let purchase_order = &mut ctx.accounts.purchase_order;
+    let shipment = &mut ctx.accounts.shipment;
+    let logistics = &mut ctx.accounts.logistics;
+    let warehouse = &mut ctx.accounts.warehouse;
+    let warehouse_stock = &mut ctx.accounts.warehouse_stock;
+    let factory = &mut ctx.accounts.factory;
+    let user = &ctx.accounts.user;
+
+    require!(
+        user.has_role(Role::Warehouse) && user.owner == warehouse.owner,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let signer = ctx.accounts.signer.key();
+    match purchase_order.status {
+        // Disputes are settled in the factory's favour by the platform owner
+        PurchaseOrderStatus::Disputed => require!(
+            signer == ctx.accounts.program_state.owner,
+            SupplyChainErrors::UnauthorizedAccess
+        ),
+        _ => require!(
+            warehouse.owner == signer,
+            SupplyChainErrors::UnauthorizedAccess
+        ),
+    }
+    require!(
+        shipment.status == ShipmentStatus::InTransit,
+        SupplyChainErrors::ShipmentNotInTransit
+    );
+    // The carrier must have handed the goods over before payment is released
+    require!(
+        shipment.custodian == warehouse.owner,
+        SupplyChainErrors::InvalidCustodian
+    );
+    purchase_order.transition_to(PurchaseOrderStatus::Received)?;
+
+    let amount = purchase_order.total_price;
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        ctx.accounts
+            .program_state
+            .purchase_fee_for(BalanceHolderKind::Factory, amount)?
+    };
+    let factory_amount = amount - platform_fee;
+    if purchase_order.payment_mint == Pubkey::default() {
+        **purchase_order.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **factory.to_account_info().try_borrow_mut_lamports()? += factory_amount;
+        **ctx
+            .accounts
+            .treasury
+            .to_account_info()
+            .try_borrow_mut_lamports()? += platform_fee;
+        factory.balance = factory
+            .balance
+            .checked_add(factory_amount)
+            .ok_or(SupplyChainErrors::Overflow)?;
+    } else {
+        let (
+            Some(escrow_vault),
+            Some(factory_vault),
+            Some(treasury_vault),
+            Some(vault_authority),
+            Some(vault_authority_bump),
+            Some(payment_mint),
+            Some(token_program),
+        ) = (
+            ctx.accounts.escrow_vault.as_ref(),
+            ctx.accounts.factory_vault.as_ref(),
+            ctx.accounts.treasury_vault.as_ref(),
+            ctx.accounts.vault_authority.as_ref(),
+            ctx.bumps.vault_authority,
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )
+        else {
+            return Err(SupplyChainErrors::InvalidTokenVault.into());
+        };
+        require_keys_eq!(
+            payment_mint.key(),
+            purchase_order.payment_mint,
+            SupplyChainErrors::InvalidPaymentMint
+        );
+        transfer_from_vault(
+            escrow_vault,
+            factory_vault,
+            payment_mint,
+            vault_authority,
+            vault_authority_bump,
+            token_program,
+            factory_amount,
+        )?;
+        if platform_fee > 0 {
+            transfer_from_vault(
+                escrow_vault,
+                treasury_vault,
+                payment_mint,
+                vault_authority,
+                vault_authority_bump,
+                token_program,
+                platform_fee,
+            )?;
+        }
+    }
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let product = &ctx.accounts.product;
+    // Capacity was already reserved when the order was placed
+    if warehouse_stock.created_at == 0 {
+        warehouse_stock.warehouse_id = warehouse.warehouse_id;
+        warehouse_stock.warehouse_pda = warehouse.key();
+        warehouse_stock.product_id = product.product_id;
+        warehouse_stock.product_pda = product.key();
+        warehouse_stock.created_at = now;
+    }
+    warehouse_stock.receive(purchase_order.quantity, amount)?;
+    warehouse_stock.updated_at = now;
+    warehouse.product_count = warehouse
+        .product_count
+        .checked_add(purchase_order.quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    shipment.status = ShipmentStatus::Delivered;
+    shipment.shipment_ended_at = now;
+    logistics.active_shipments = logistics.active_shipments.saturating_sub(1);
+    purchase_order.updated_at = now;
+
+    emit_cpi!(PurchaseOrderStatusChanged {
+        purchase_order: purchase_order.key(),
+        status: purchase_order.status,
+        timestamp: now,
+    });
+    if platform_fee > 0 {
+        emit_cpi!(PlatformFeeCollected {
+            payer: purchase_order.key(),
+            payee: factory.key(),
+            amount: platform_fee,
+            timestamp: now,
+        });
+    }
+    emit_cpi!(WarehousePurchase {
+        warehouse: warehouse.key(),
+        product: product.key(),
+        factory: factory.key(),
+        quantity: purchase_order.quantity,
+        amount,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReceivePurchaseOrderCtx<'info> {
+    // The warehouse owner, or the platform owner when settling a dispute
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub user: Box<Account<'info, User>>,
+    #[account(mut)]
+    pub purchase_order: Box<Account<'info, PurchaseOrder>>,
+    #[account(
+        mut,
+        seeds = [b"shipment", purchase_order.key().as_ref()],
+        bump,
+    )]
+    pub shipment: Box<Account<'info, Shipment>>,
+    #[account(mut, address = shipment.provider_pda)]
+    pub logistics: Box<Account<'info, LogisticsProvider>>,
+    #[account(
+        mut,
+        address = purchase_order.warehouse_pda,
+        constraint = !warehouse.frozen @ SupplyChainErrors::AccountFrozen,
+    )]
+    pub warehouse: Box<Account<'info, Warehouse>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + WarehouseStock::INIT_SPACE,
+        seeds = [b"warehouse_stock", warehouse.key().as_ref(), product.key().as_ref()],
+        bump,
+    )]
+    pub warehouse_stock: Box<Account<'info, WarehouseStock>>,
+    #[account(address = purchase_order.product_pda)]
+    pub product: Box<Account<'info, Product>>,
+    #[account(mut, address = purchase_order.factory_pda)]
+    pub factory: Box<Account<'info, Factory>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", factory.owner.as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
+    // Token accounts below are only required when the order was paid with the payment mint
+    #[account(mut, seeds = [b"vault", purchase_order.key().as_ref()], bump)]
+    pub escrow_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, seeds = [b"vault", factory.key().as_ref()], bump)]
+    pub factory_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, seeds = [b"vault", treasury.key().as_ref()], bump)]
+    pub treasury_vault: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::errors::SupplyChainErrors;
+use crate::events::CheckpointRecorded;
+use crate::states::{
+    shipment::{Shipment, ShipmentStatus},
+    shipment_checkpoint::{CheckpointKind, ShipmentCheckpoint},
+};
+
+pub fn record_checkpoint(
+    ctx: Context<RecordCheckpointCtx>,
+    location: String,
+    latitude: f64,
+    longitude: f64,
+    condition: String,
+) -> Result<()> {
+    let shipment = &mut ctx.accounts.shipment;
+    let checkpoint = &mut ctx.accounts.checkpoint;
+    let custodian = ctx.accounts.custodian.key();
+
+    require!(
+        shipment.status == ShipmentStatus::InTransit,
+        SupplyChainErrors::ShipmentNotInTransit
+    );
+    require!(
+        shipment.custodian == custodian,
+        SupplyChainErrors::InvalidCustodian
+    );
+    require!(location.len() <= 64, SupplyChainErrors::InvalidLocation);
+    require!(condition.len() <= 128, SupplyChainErrors::InvalidCondition);
+
+    checkpoint.checkpoint_id = shipment.checkpoint_count + 1;
+    checkpoint.shipment_pda = shipment.key();
+    checkpoint.kind = CheckpointKind::Checkpoint;
+    checkpoint.recorded_by = custodian;
+    checkpoint.from_custodian = custodian;
+    checkpoint.to_custodian = custodian;
+    checkpoint.location = location;
+    checkpoint.latitude = latitude;
+    checkpoint.longitude = longitude;
+    checkpoint.condition = condition;
+    checkpoint.timestamp = Clock::get()?.unix_timestamp as u64;
+
+    shipment.checkpoint_count += 1;
+
+    emit_cpi!(CheckpointRecorded {
+        shipment: shipment.key(),
+        checkpoint: checkpoint.key(),
+        custodian,
+        timestamp: checkpoint.timestamp,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordCheckpointCtx<'info> {
+    #[account(mut)]
+    pub custodian: Signer<'info>,
+    #[account(mut)]
+    pub shipment: Account<'info, Shipment>,
+    #[account(
+        init,
+        payer = custodian,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ShipmentCheckpoint::INIT_SPACE,
+        seeds = [b"checkpoint", shipment.key().as_ref(), (shipment.checkpoint_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, ShipmentCheckpoint>,
+    pub system_program: Program<'info, System>,
+}
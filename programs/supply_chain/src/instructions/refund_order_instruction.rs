@@ -0,0 +1,190 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::errors::SupplyChainErrors;
+use crate::events::OrderRefunded;
+use crate::instructions::token_vault_instruction::transfer_from_vault;
+use crate::states::{
+    order::{Order, OrderStatus},
+    order_escrow::OrderEscrow,
+    program_state::ProgramState,
+    seller::Seller,
+    transaction::Transaction,
+    user::{Role, User},
+    warehouse_stock::WarehouseStock,
+};
+
+pub fn refund_order_instruction(ctx: Context<RefundOrderCtx>) -> Result<()> {
+    let user = &mut ctx.accounts.user;
+    let seller = &ctx.accounts.seller;
+    let order = &mut ctx.accounts.order;
+    let escrow = &mut ctx.accounts.escrow;
+    let warehouse_stock = &mut ctx.accounts.warehouse_stock;
+    let transaction = &mut ctx.accounts.transaction;
+    let program_state = &ctx.accounts.program_state;
+    let authority = ctx.accounts.authority.key();
+    let now = Clock::get()?.unix_timestamp as u64;
+
+    require!(
+        user.has_role(Role::Seller) && user.owner == seller.owner,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        order.seller_pda == seller.key(),
+        SupplyChainErrors::InvalidOrder
+    );
+    require!(
+        !escrow.released && !escrow.refunded,
+        SupplyChainErrors::EscrowAlreadySettled
+    );
+    match order.status {
+        // Disputes are settled by the platform owner
+        OrderStatus::Disputed => require!(
+            authority == program_state.owner,
+            SupplyChainErrors::UnauthorizedAccess
+        ),
+        // Orders the warehouse never shipped can be refunded once the deadline passes
+        OrderStatus::Ordered | OrderStatus::Accepted => {
+            require!(
+                authority == seller.owner,
+                SupplyChainErrors::UnauthorizedAccess
+            );
+            require!(
+                now > order.ship_by,
+                SupplyChainErrors::OrderDeadlineNotReached
+            );
+            // The units were still reserved for this order
+            warehouse_stock.release(order.product_stock)?;
+            warehouse_stock.updated_at = now;
+        }
+        _ => require!(
+            authority == seller.owner,
+            SupplyChainErrors::UnauthorizedAccess
+        ),
+    }
+    order.transition_to(OrderStatus::Refunded)?;
+
+    let amount = escrow.amount;
+    let recipient = if escrow.payment_mint == Pubkey::default() {
+        **escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .seller_owner
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+        ctx.accounts.seller_owner.key()
+    } else {
+        let (
+            Some(escrow_vault),
+            Some(refund_token_account),
+            Some(vault_authority),
+            Some(vault_authority_bump),
+            Some(payment_mint),
+            Some(token_program),
+        ) = (
+            ctx.accounts.escrow_vault.as_ref(),
+            ctx.accounts.refund_token_account.as_ref(),
+            ctx.accounts.vault_authority.as_ref(),
+            ctx.bumps.vault_authority,
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )
+        else {
+            return Err(SupplyChainErrors::InvalidTokenVault.into());
+        };
+        require_keys_eq!(
+            payment_mint.key(),
+            escrow.payment_mint,
+            SupplyChainErrors::InvalidPaymentMint
+        );
+        require_keys_eq!(
+            refund_token_account.owner,
+            seller.owner,
+            SupplyChainErrors::UnauthorizedAccess
+        );
+        transfer_from_vault(
+            escrow_vault,
+            refund_token_account,
+            payment_mint,
+            vault_authority,
+            vault_authority_bump,
+            token_program,
+            amount,
+        )?;
+        refund_token_account.key()
+    };
+    escrow.amount = 0;
+    escrow.refunded = true;
+
+    transaction.transaction_id = user
+        .transaction_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    transaction.from = escrow.key();
+    transaction.to = recipient;
+    transaction.amount = amount;
+    transaction.timestamp = now;
+    transaction.status = true;
+
+    user.transaction_count = user
+        .transaction_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(OrderRefunded {
+        order: order.key(),
+        recipient,
+        amount,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefundOrderCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [b"transaction", user.key().as_ref(), (user.transaction_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub transaction: Account<'info, Transaction>,
+    #[account(mut)]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, OrderEscrow>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", order.warehouse_pda.as_ref(), order.product_pda.as_ref()],
+        bump,
+    )]
+    pub warehouse_stock: Account<'info, WarehouseStock>,
+    pub seller: Account<'info, Seller>,
+    /// CHECK: Refund destination, validated against seller.owner
+    #[account(mut, address = seller.owner)]
+    pub seller_owner: AccountInfo<'info>,
+    #[account(mut)]
+    pub user: Account<'info, User>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    // Token accounts below are only required when the order was paid with the payment mint
+    #[account(mut, seeds = [b"vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub refund_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
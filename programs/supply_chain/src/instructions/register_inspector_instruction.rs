@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    errors::SupplyChainErrors,
+    events::{InspectorFeeChanged, InspectorRegistered},
+    states::{
+        inspector_profile::InspectorProfile,
+        user::{Role, User},
+    },
+};
+
+pub fn register_inspector(
+    ctx: Context<RegisterInspectorCtx>,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    fee_charge_per_product: u64,
+) -> Result<()> {
+    let inspector = &mut ctx.accounts.inspector;
+    require!(
+        ctx.accounts.user.has_role(Role::Inspector),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(name.len() <= 32, SupplyChainErrors::InvalidName);
+
+    inspector.owner = ctx.accounts.owner.key();
+    inspector.name = name;
+    inspector.latitude = latitude;
+    inspector.longitude = longitude;
+    inspector.fee_charge_per_product = fee_charge_per_product;
+    inspector.balance = 0;
+    inspector.inspection_count = 0;
+    inspector.created_at = Clock::get()?.unix_timestamp as u64;
+
+    emit_cpi!(InspectorRegistered {
+        inspector: inspector.key(),
+        owner: inspector.owner,
+        fee_charge_per_product,
+        timestamp: inspector.created_at,
+    });
+    Ok(())
+}
+
+// Only affects inspections made after the change, earlier records keep their fee
+pub fn update_inspector_fee(
+    ctx: Context<UpdateInspectorFeeCtx>,
+    fee_charge_per_product: u64,
+) -> Result<()> {
+    let inspector = &mut ctx.accounts.inspector;
+    let old_fee = inspector.fee_charge_per_product;
+    inspector.fee_charge_per_product = fee_charge_per_product;
+
+    emit_cpi!(InspectorFeeChanged {
+        inspector: inspector.key(),
+        old_fee,
+        new_fee: fee_charge_per_product,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterInspectorCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [b"user", owner.key().as_ref()], bump)]
+    pub user: Account<'info, User>,
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + InspectorProfile::INIT_SPACE,
+        seeds = [b"inspector_profile", owner.key().as_ref()],
+        bump,
+    )]
+    pub inspector: Account<'info, InspectorProfile>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateInspectorFeeCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"inspector_profile", owner.key().as_ref()],
+        bump,
+    )]
+    pub inspector: Account<'info, InspectorProfile>,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::events::WarehouseResized;
+use crate::states::{
+    user::{Role, User},
+    warehouse::Warehouse,
+};
+
+pub fn resize_warehouse_instruction(
+    ctx: Context<ResizeWarehouseCtx>,
+    warehouse_size: u64,
+) -> Result<()> {
+    let user = &ctx.accounts.user;
+    let warehouse = &mut ctx.accounts.warehouse;
+    let owner = ctx.accounts.owner.key();
+
+    require!(
+        user.has_role(Role::Warehouse) && user.owner == owner,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        warehouse.owner == owner,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        warehouse_size >= warehouse.used_capacity,
+        SupplyChainErrors::InvalidWarehouseSize
+    );
+
+    let old_size = warehouse.warehouse_size;
+    warehouse.warehouse_size = warehouse_size;
+
+    emit_cpi!(WarehouseResized {
+        warehouse: warehouse.key(),
+        old_size,
+        new_size: warehouse_size,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResizeWarehouseCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub warehouse: Account<'info, Warehouse>,
+    pub user: Account<'info, User>,
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    errors::SupplyChainErrors,
+    events::BatchCreated,
+    states::{batch::Batch, factory::Factory, product::Product},
+};
+
+pub fn restock_product(
+    ctx: Context<RestockProductCtx>,
+    batch_number: String,
+    quantity: u64,
+    raw_material_used: u64,
+    produced_at: u64,
+    expires_at: u64,
+) -> Result<()> {
+    let batch = &mut ctx.accounts.batch;
+    let product = &mut ctx.accounts.product;
+    let factory = &ctx.accounts.factory;
+    require!(
+        factory.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        product.factory_pda == factory.key(),
+        SupplyChainErrors::InvalidFactory
+    );
+    require!(
+        batch_number.len() <= 32,
+        SupplyChainErrors::InvalidBatchNumber
+    );
+    require!(quantity > 0, SupplyChainErrors::InsufficientStock);
+    let now = Clock::get()?.unix_timestamp as u64;
+    require!(
+        produced_at <= now && expires_at > now,
+        SupplyChainErrors::InvalidBatchDates
+    );
+
+    batch.batch_id = product
+        .batch_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    batch.product_pda = product.key();
+    batch.batch_number = batch_number;
+    batch.quantity = quantity;
+    batch.raw_material_used = raw_material_used;
+    batch.produced_at = produced_at;
+    batch.expires_at = expires_at;
+    batch.inspected = false;
+    batch.created_at = now;
+    product.batch_count = batch.batch_id;
+    product.raw_material_used = product
+        .raw_material_used
+        .checked_add(raw_material_used)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(BatchCreated {
+        batch: batch.key(),
+        product: product.key(),
+        batch_id: batch.batch_id,
+        quantity,
+        expires_at,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RestockProductCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Batch::INIT_SPACE,
+        seeds = [b"batch", product.key().as_ref(), (product.batch_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub batch: Account<'info, Batch>,
+    #[account(mut)]
+    pub product: Account<'info, Product>,
+    pub factory: Account<'info, Factory>,
+    pub system_program: Program<'info, System>,
+}
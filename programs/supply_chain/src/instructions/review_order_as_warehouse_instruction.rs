@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::events::OrderStatusChanged;
+use crate::states::{
+    order::{Order, OrderStatus},
+    user::{Role, User},
+    warehouse::Warehouse,
+    warehouse_stock::WarehouseStock,
+};
+
+pub fn accept_order_instruction_as_warehouse(
+    ctx: Context<ReviewOrderAsWarehouseCtx>,
+) -> Result<()> {
+    validate_warehouse_reviewer(&ctx)?;
+    let order = &mut ctx.accounts.order;
+    order.transition_to(OrderStatus::Accepted)?;
+
+    emit_cpi!(OrderStatusChanged {
+        order: order.key(),
+        status: order.status,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+pub fn reject_order_instruction_as_warehouse(
+    ctx: Context<ReviewOrderAsWarehouseCtx>,
+) -> Result<()> {
+    validate_warehouse_reviewer(&ctx)?;
+    let order = &mut ctx.accounts.order;
+    let warehouse_stock = &mut ctx.accounts.warehouse_stock;
+    order.transition_to(OrderStatus::Rejected)?;
+    // Rejected units become available to other sellers again
+    warehouse_stock.release(order.product_stock)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    warehouse_stock.updated_at = now;
+
+    emit_cpi!(OrderStatusChanged {
+        order: order.key(),
+        status: order.status,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+fn validate_warehouse_reviewer(ctx: &Context<ReviewOrderAsWarehouseCtx>) -> Result<()> {
+    let user = &ctx.accounts.user;
+    let warehouse = &ctx.accounts.warehouse;
+    let signer = ctx.accounts.signer.key();
+    require!(
+        user.has_role(Role::Warehouse) && user.owner == signer,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        warehouse.owner == signer,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReviewOrderAsWarehouseCtx<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub order: Account<'info, Order>,
+    #[account(address = order.warehouse_pda)]
+    pub warehouse: Account<'info, Warehouse>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", warehouse.key().as_ref(), order.product_pda.as_ref()],
+        bump,
+    )]
+    pub warehouse_stock: Account<'info, WarehouseStock>,
+    pub user: Account<'info, User>,
+}
@@ -0,0 +1,275 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::errors::SupplyChainErrors;
+use crate::events::{PurchaseOrderRefunded, PurchaseOrderStatusChanged};
+use crate::instructions::token_vault_instruction::transfer_from_vault;
+use crate::states::{
+    batch::Batch,
+    factory::Factory,
+    logistics_provider::LogisticsProvider,
+    product::Product,
+    program_state::ProgramState,
+    purchase_order::{PurchaseOrder, PurchaseOrderStatus},
+    shipment::{Shipment, ShipmentStatus},
+    user::{Role, User},
+    warehouse::Warehouse,
+};
+
+pub fn accept_purchase_order(ctx: Context<AcceptPurchaseOrderCtx>) -> Result<()> {
+    require!(
+        ctx.accounts.factory.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let purchase_order = &mut ctx.accounts.purchase_order;
+    purchase_order.transition_to(PurchaseOrderStatus::Accepted)?;
+    purchase_order.updated_at = Clock::get()?.unix_timestamp as u64;
+
+    emit_cpi!(PurchaseOrderStatusChanged {
+        purchase_order: purchase_order.key(),
+        status: purchase_order.status,
+        timestamp: purchase_order.updated_at,
+    });
+    Ok(())
+}
+
+// Declined by the factory, the escrow goes back to the warehouse owner
+pub fn reject_purchase_order(ctx: Context<ClosePurchaseOrderCtx>) -> Result<()> {
+    require!(
+        ctx.accounts.factory.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    close_purchase_order(ctx, PurchaseOrderStatus::Rejected)
+}
+
+// Withdrawn by the warehouse before the factory accepted it
+pub fn cancel_purchase_order(ctx: Context<ClosePurchaseOrderCtx>) -> Result<()> {
+    require!(
+        ctx.accounts.warehouse.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    close_purchase_order(ctx, PurchaseOrderStatus::Cancelled)
+}
+
+// Accepted orders the factory never shipped can be refunded by the warehouse once the
+// deadline passes, disputed ones are refunded by the platform owner
+pub fn refund_purchase_order(ctx: Context<ClosePurchaseOrderCtx>) -> Result<()> {
+    let signer = ctx.accounts.signer.key();
+    match ctx.accounts.purchase_order.status {
+        PurchaseOrderStatus::Disputed => require!(
+            signer == ctx.accounts.program_state.owner,
+            SupplyChainErrors::UnauthorizedAccess
+        ),
+        _ => {
+            require!(
+                signer == ctx.accounts.warehouse.owner,
+                SupplyChainErrors::UnauthorizedAccess
+            );
+            require!(
+                Clock::get()?.unix_timestamp as u64 > ctx.accounts.purchase_order.ship_by,
+                SupplyChainErrors::OrderDeadlineNotReached
+            );
+        }
+    }
+    close_purchase_order(ctx, PurchaseOrderStatus::Refunded)
+}
+
+// Only shipped goods can be disputed, the platform owner then refunds the escrow or
+// releases it to the factory
+pub fn dispute_purchase_order(ctx: Context<DisputePurchaseOrderCtx>) -> Result<()> {
+    let user = &ctx.accounts.user;
+    let signer = ctx.accounts.signer.key();
+    require!(
+        user.has_role(Role::Warehouse) && user.owner == signer,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        ctx.accounts.warehouse.owner == signer,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let purchase_order = &mut ctx.accounts.purchase_order;
+    purchase_order.transition_to(PurchaseOrderStatus::Disputed)?;
+    purchase_order.updated_at = Clock::get()?.unix_timestamp as u64;
+
+    emit_cpi!(PurchaseOrderStatusChanged {
+        purchase_order: purchase_order.key(),
+        status: purchase_order.status,
+        timestamp: purchase_order.updated_at,
+    });
+    Ok(())
+}
+
+fn close_purchase_order(
+    ctx: Context<ClosePurchaseOrderCtx>,
+    status: PurchaseOrderStatus,
+) -> Result<()> {
+    let purchase_order = &mut ctx.accounts.purchase_order;
+    let warehouse = &mut ctx.accounts.warehouse;
+    let product = &mut ctx.accounts.product;
+    purchase_order.transition_to(status)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+
+    // The reserved units and warehouse space are released, goods shipped before a dispute go
+    // back to the factory
+    product.product_stock = product
+        .product_stock
+        .checked_add(purchase_order.quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    warehouse.free(product.unit_volume.saturating_mul(purchase_order.quantity));
+    if purchase_order.batch_pda != Pubkey::default() {
+        let Some(batch) = ctx.accounts.batch.as_mut() else {
+            return Err(SupplyChainErrors::BatchNotReleased.into());
+        };
+        batch.remaining = batch
+            .remaining
+            .checked_add(purchase_order.quantity)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        product.batch_stock = product
+            .batch_stock
+            .checked_add(purchase_order.quantity)
+            .ok_or(SupplyChainErrors::Overflow)?;
+    }
+    // A shipment still on the road when a disputed order is refunded is returned, which also
+    // stops any further custody handoff
+    if purchase_order.shipment_pda != Pubkey::default() {
+        let (Some(shipment), Some(logistics)) = (
+            ctx.accounts.shipment.as_mut(),
+            ctx.accounts.logistics.as_mut(),
+        ) else {
+            return Err(SupplyChainErrors::InvalidLogistics.into());
+        };
+        require_keys_eq!(
+            logistics.key(),
+            shipment.provider_pda,
+            SupplyChainErrors::InvalidLogistics
+        );
+        if shipment.status == ShipmentStatus::InTransit {
+            shipment.status = ShipmentStatus::Returned;
+            shipment.shipment_ended_at = now;
+            logistics.active_shipments = logistics.active_shipments.saturating_sub(1);
+        }
+    }
+
+    let amount = purchase_order.total_price;
+    let recipient = if purchase_order.payment_mint == Pubkey::default() {
+        **purchase_order.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .warehouse_owner
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+        ctx.accounts.warehouse_owner.key()
+    } else {
+        let (
+            Some(escrow_vault),
+            Some(refund_token_account),
+            Some(vault_authority),
+            Some(vault_authority_bump),
+            Some(payment_mint),
+            Some(token_program),
+        ) = (
+            ctx.accounts.escrow_vault.as_ref(),
+            ctx.accounts.refund_token_account.as_ref(),
+            ctx.accounts.vault_authority.as_ref(),
+            ctx.bumps.vault_authority,
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )
+        else {
+            return Err(SupplyChainErrors::InvalidTokenVault.into());
+        };
+        require_keys_eq!(
+            payment_mint.key(),
+            purchase_order.payment_mint,
+            SupplyChainErrors::InvalidPaymentMint
+        );
+        require_keys_eq!(
+            refund_token_account.owner,
+            warehouse.owner,
+            SupplyChainErrors::UnauthorizedAccess
+        );
+        transfer_from_vault(
+            escrow_vault,
+            refund_token_account,
+            payment_mint,
+            vault_authority,
+            vault_authority_bump,
+            token_program,
+            amount,
+        )?;
+        refund_token_account.key()
+    };
+    purchase_order.updated_at = now;
+
+    emit_cpi!(PurchaseOrderStatusChanged {
+        purchase_order: purchase_order.key(),
+        status: purchase_order.status,
+        timestamp: now,
+    });
+    emit_cpi!(PurchaseOrderRefunded {
+        purchase_order: purchase_order.key(),
+        recipient,
+        amount,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptPurchaseOrderCtx<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub purchase_order: Account<'info, PurchaseOrder>,
+    #[account(address = purchase_order.factory_pda)]
+    pub factory: Account<'info, Factory>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClosePurchaseOrderCtx<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub purchase_order: Box<Account<'info, PurchaseOrder>>,
+    #[account(address = purchase_order.factory_pda)]
+    pub factory: Box<Account<'info, Factory>>,
+    #[account(mut, address = purchase_order.warehouse_pda)]
+    pub warehouse: Box<Account<'info, Warehouse>>,
+    #[account(mut, address = purchase_order.product_pda)]
+    pub product: Box<Account<'info, Product>>,
+    // Only required when the units were reserved from a restocked batch
+    #[account(mut, address = purchase_order.batch_pda)]
+    pub batch: Option<Box<Account<'info, Batch>>>,
+    // Only required once the order was dispatched, the carrier is checked against the shipment
+    #[account(mut, address = purchase_order.shipment_pda)]
+    pub shipment: Option<Box<Account<'info, Shipment>>>,
+    #[account(mut)]
+    pub logistics: Option<Box<Account<'info, LogisticsProvider>>>,
+    /// CHECK: Refund destination, validated against warehouse.owner
+    #[account(mut, address = warehouse.owner)]
+    pub warehouse_owner: AccountInfo<'info>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    // Token accounts below are only required when the order was paid with the payment mint
+    #[account(mut, seeds = [b"vault", purchase_order.key().as_ref()], bump)]
+    pub escrow_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub refund_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DisputePurchaseOrderCtx<'info> {
+    pub signer: Signer<'info>,
+    pub user: Account<'info, User>,
+    #[account(mut)]
+    pub purchase_order: Account<'info, PurchaseOrder>,
+    #[account(address = purchase_order.warehouse_pda)]
+    pub warehouse: Account<'info, Warehouse>,
+}
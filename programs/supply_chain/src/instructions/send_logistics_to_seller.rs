@@ -1,11 +1,25 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
 use crate::{
     constants::ANCHOR_DISCRIMINATOR_SIZE,
     errors::SupplyChainErrors,
+    events::{OrderStatusChanged, PlatformFeeCollected, ShipmentDispatched},
+    instructions::token_vault_instruction::transfer_tokens,
     states::{
-        logistics::Logistics, order::Order, product::Product, transaction::Transaction, user::User,
+        balance_holder::BalanceHolderKind,
+        fee_exemption::FeeExemption,
+        logistics_provider::LogisticsProvider,
+        order::{Order, OrderStatus},
+        product::Product,
+        program_state::ProgramState,
+        shipment::{Shipment, ShipmentStatus},
+        transaction::Transaction,
+        treasury::Treasury,
+        user::{Role, User},
         warehouse::Warehouse,
+        warehouse_stock::WarehouseStock,
     },
 };
 
@@ -22,6 +36,8 @@ pub fn send_logistics_to_seller_instruction(
     let warehouse = &mut ctx.accounts.warehouse;
     let product = &mut ctx.accounts.product;
     let order = &mut ctx.accounts.order;
+    let warehouse_stock = &mut ctx.accounts.warehouse_stock;
+    let shipment = &mut ctx.accounts.shipment;
 
     require!(
         product_id == product.product_id,
@@ -36,63 +52,196 @@ pub fn send_logistics_to_seller_instruction(
         SupplyChainErrors::InvalidLogistics
     );
     require!(
-        user.role == "WAREHOUSE",
+        user.has_role(Role::Warehouse),
         SupplyChainErrors::UnauthorizedAccess
     );
     require!(
-        order.warehouse_id == warehouse.warehouse_id,
+        warehouse.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        order.warehouse_pda == warehouse.key(),
         SupplyChainErrors::InvalidWarehouse
     );
     require!(
-        order.product_id == warehouse.product_id,
+        order.product_pda == product.key(),
         SupplyChainErrors::InvalidProductId
     );
+    order.transition_to(OrderStatus::Shipped)?;
+
     let total_amount_to_pay = shipping_cost;
-    let transaction_instruction = anchor_lang::solana_program::system_instruction::transfer(
-        &ctx.accounts.signer.key(),
-        &logistics.key(),
-        total_amount_to_pay,
-    );
-    let result = anchor_lang::solana_program::program::invoke(
-        &transaction_instruction,
-        &[
-            ctx.accounts.signer.to_account_info(),
-            logistics.to_account_info(),
-        ],
-    );
-    if let Err(e) = result {
-        msg!("Transaction to logistics failed: {:?}", e);
-        return Err(e.into());
-    }
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        ctx.accounts
+            .program_state
+            .purchase_fee_for(BalanceHolderKind::Logistics, total_amount_to_pay)?
+    };
+    let logistics_amount = total_amount_to_pay - platform_fee;
+    // Lamports go to the carrier account, tokens to its vault
+    let payment_destination = match ctx.accounts.payment_mint.as_ref() {
+        None => {
+            let transaction_instruction = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.signer.key(),
+                &logistics.key(),
+                logistics_amount,
+            );
+            let result = anchor_lang::solana_program::program::invoke(
+                &transaction_instruction,
+                &[
+                    ctx.accounts.signer.to_account_info(),
+                    logistics.to_account_info(),
+                ],
+            );
+            if let Err(e) = result {
+                msg!("Transaction to logistics failed: {:?}", e);
+                return Err(e.into());
+            }
+            if platform_fee > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.signer.to_account_info(),
+                            to: ctx.accounts.treasury.to_account_info(),
+                        },
+                    ),
+                    platform_fee,
+                )?;
+            }
+            logistics.balance += logistics_amount;
+            logistics.key()
+        }
+        Some(payment_mint) => {
+            let (
+                Some(payer_token_account),
+                Some(logistics_vault),
+                Some(treasury_vault),
+                Some(token_program),
+            ) = (
+                ctx.accounts.payer_token_account.as_ref(),
+                ctx.accounts.logistics_vault.as_ref(),
+                ctx.accounts.treasury_vault.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            )
+            else {
+                return Err(SupplyChainErrors::InvalidTokenVault.into());
+            };
+            transfer_tokens(
+                payer_token_account,
+                logistics_vault,
+                payment_mint,
+                &ctx.accounts.signer,
+                token_program,
+                logistics_amount,
+            )?;
+            if platform_fee > 0 {
+                transfer_tokens(
+                    payer_token_account,
+                    treasury_vault,
+                    payment_mint,
+                    &ctx.accounts.signer,
+                    token_program,
+                    platform_fee,
+                )?;
+            }
+            logistics_vault.key()
+        }
+    };
+    let now = Clock::get()?.unix_timestamp as u64;
     transaction.transaction_id = user.transaction_count + 1;
     transaction.from = ctx.accounts.signer.key();
-    transaction.to = logistics.key();
+    transaction.to = payment_destination;
     transaction.amount = total_amount_to_pay;
-    transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+    transaction.timestamp = now;
     transaction.status = true;
 
-    logistics.shipment_started_at = Clock::get()?.unix_timestamp as u64;
-    logistics.product_stock = order.product_stock;
-    logistics.status = String::from("ON THE WAY");
-    logistics.balance += total_amount_to_pay;
+    shipment.shipment_id = logistics.shipment_count + 1;
+    shipment.order_pda = order.key();
+    shipment.provider_pda = logistics.key();
+    shipment.warehouse_pda = warehouse.key();
+    shipment.seller_pda = order.seller_pda;
+    shipment.product_id = product.product_id;
+    shipment.product_pda = product.key();
+    shipment.product_stock = order.product_stock;
+    shipment.shipment_cost = total_amount_to_pay;
+    shipment.status = ShipmentStatus::InTransit;
+    // The warehouse keeps custody until it hands the goods to the carrier
+    shipment.custodian = warehouse.owner;
+    shipment.checkpoint_count = 0;
+    shipment.shipment_started_at = now;
+    shipment.shipment_ended_at = 0;
+
+    logistics.shipment_count += 1;
+    logistics.active_shipments += 1;
 
     warehouse.logistic_count += 1;
-    warehouse.product_count -= order.product_stock;
+    warehouse_stock.dispatch(order.product_stock)?;
+    warehouse_stock.updated_at = now;
+    warehouse.product_count = warehouse
+        .product_count
+        .checked_sub(order.product_stock)
+        .ok_or(SupplyChainErrors::InsufficientStock)?;
+    warehouse.free(product.unit_volume.saturating_mul(order.product_stock));
 
     order.logistic_id = logistics.logistic_id;
     order.product_pda = product.key();
     order.logistic_pda = logistics.key();
+    order.shipment_pda = shipment.key();
 
     user.transaction_count += 1;
+
+    emit_cpi!(OrderStatusChanged {
+        order: order.key(),
+        status: order.status,
+        timestamp: now,
+    });
+    if platform_fee > 0 {
+        emit_cpi!(PlatformFeeCollected {
+            payer: ctx.accounts.signer.key(),
+            payee: logistics.key(),
+            amount: platform_fee,
+            timestamp: now,
+        });
+    }
+    emit_cpi!(ShipmentDispatched {
+        shipment: shipment.key(),
+        order: order.key(),
+        provider: logistics.key(),
+        warehouse: warehouse.key(),
+        quantity: shipment.product_stock,
+        shipment_cost: total_amount_to_pay,
+        timestamp: now,
+    });
     Ok(())
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SendLogisticsToSellerCtx<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
-    #[account(mut)]
-    pub logistics: Account<'info, Logistics>,
+    #[account(mut, constraint = !logistics.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub logistics: Box<Account<'info, LogisticsProvider>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", logistics.owner.as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Shipment::INIT_SPACE,
+        seeds = [b"shipment", order.key().as_ref()],
+        bump,
+    )]
+    pub shipment: Box<Account<'info, Shipment>>,
     #[account(
         init,
         payer = signer,
@@ -100,14 +249,44 @@ pub struct SendLogisticsToSellerCtx<'info> {
         seeds = [b"transaction",user.key().as_ref(),(user.transaction_count+1).to_le_bytes().as_ref()],
         bump,
     )]
-    pub transaction: Account<'info, Transaction>,
-    #[account(mut)]
-    pub warehouse: Account<'info, Warehouse>,
+    pub transaction: Box<Account<'info, Transaction>>,
+    #[account(mut, constraint = !warehouse.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub warehouse: Box<Account<'info, Warehouse>>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", warehouse.key().as_ref(), product.key().as_ref()],
+        bump,
+    )]
+    pub warehouse_stock: Box<Account<'info, WarehouseStock>>,
     #[account(mut)]
-    pub product: Account<'info, Product>,
+    pub product: Box<Account<'info, Product>>,
     #[account(mut)]
-    pub user: Account<'info, User>,
+    pub user: Box<Account<'info, User>>,
     #[account(mut)]
-    pub order: Account<'info, Order>,
+    pub order: Box<Account<'info, Order>>,
+    // Token accounts below are only required when paying with the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = signer,
+    )]
+    pub payer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"vault", logistics.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+    )]
+    pub logistics_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"vault", treasury.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+    )]
+    pub treasury_vault: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(address = program_state.payment_mint @ SupplyChainErrors::InvalidPaymentMint)]
+    pub payment_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::errors::SupplyChainErrors;
+use crate::events::{PaymentMintChanged, TokenVaultCreated};
+use crate::states::program_state::ProgramState;
+
+// The mint can only be set once: vaults live at [b"vault", holder] whatever their mint, so
+// changing it would strand every balance held in an existing vault
+pub fn set_payment_mint(ctx: Context<SetPaymentMintCtx>) -> Result<()> {
+    let state = &mut ctx.accounts.program_state;
+    require!(
+        state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require_keys_eq!(
+        state.payment_mint,
+        Pubkey::default(),
+        SupplyChainErrors::PaymentMintAlreadySet
+    );
+    let old_mint = state.payment_mint;
+    state.payment_mint = ctx.accounts.payment_mint.key();
+
+    emit_cpi!(PaymentMintChanged {
+        old_mint,
+        new_mint: state.payment_mint,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+// Vaults are token accounts at [b"vault", holder] owned by the program wide vault authority,
+// so any factory, warehouse, seller, inspector, logistics or escrow account can hold tokens
+pub fn create_token_vault(ctx: Context<CreateTokenVaultCtx>) -> Result<()> {
+    emit_cpi!(TokenVaultCreated {
+        vault: ctx.accounts.vault.key(),
+        holder: ctx.accounts.holder.key(),
+        mint: ctx.accounts.payment_mint.key(),
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+pub(crate) fn transfer_tokens<'info>(
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    authority: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+pub(crate) fn transfer_from_vault<'info>(
+    vault: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    vault_authority: &AccountInfo<'info>,
+    vault_authority_bump: u8,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"vault_authority", &[vault_authority_bump]]];
+    token::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: vault_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPaymentMintCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    pub payment_mint: Account<'info, Mint>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateTokenVaultCtx<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Any account owned by this program, the vault is bound to it through the seeds
+    #[account(owner = crate::ID)]
+    pub holder: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"vault", holder.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = vault_authority,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+    #[account(
+        address = program_state.payment_mint @ SupplyChainErrors::InvalidPaymentMint,
+        constraint = payment_mint.key() != Pubkey::default() @ SupplyChainErrors::PaymentMintNotSet,
+    )]
+    pub payment_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,471 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    errors::SupplyChainErrors,
+    events::{
+        PlatformFeeCollected, ShipmentDispatched, WarehouseStockTransferCancelled,
+        WarehouseStockTransferReceived, WarehouseStockTransferred,
+    },
+    states::{
+        balance_holder::BalanceHolderKind,
+        fee_exemption::FeeExemption,
+        logistics_provider::LogisticsProvider,
+        product::Product,
+        program_state::ProgramState,
+        shipment::{Shipment, ShipmentStatus},
+        stock_transfer::{StockTransfer, StockTransferStatus},
+        transaction::Transaction,
+        treasury::Treasury,
+        user::{Role, User},
+        warehouse::Warehouse,
+        warehouse_stock::WarehouseStock,
+    },
+};
+
+// Moves units between two warehouses of the same owner. Without a carrier the units arrive at
+// once, with one they stay in transit until the destination confirms receipt
+pub fn transfer_warehouse_stock(
+    ctx: Context<TransferWarehouseStockCtx>,
+    quantity: u64,
+    shipping_cost: u64,
+) -> Result<()> {
+    let user = &mut ctx.accounts.user;
+    let from_warehouse = &mut ctx.accounts.from_warehouse;
+    let to_warehouse = &mut ctx.accounts.to_warehouse;
+    let from_stock = &mut ctx.accounts.from_stock;
+    let to_stock = &mut ctx.accounts.to_stock;
+    let product = &ctx.accounts.product;
+    let stock_transfer = &mut ctx.accounts.stock_transfer;
+    let transaction = &mut ctx.accounts.transaction;
+    let signer = &ctx.accounts.signer;
+
+    require!(
+        user.has_role(Role::Warehouse) && user.owner == signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        from_warehouse.owner == user.owner && to_warehouse.owner == user.owner,
+        SupplyChainErrors::InvalidStockTransfer
+    );
+    require_keys_neq!(
+        from_warehouse.key(),
+        to_warehouse.key(),
+        SupplyChainErrors::InvalidStockTransfer
+    );
+    require!(quantity > 0, SupplyChainErrors::InsufficientStock);
+    require!(
+        ctx.accounts.logistics.is_some() == ctx.accounts.shipment.is_some(),
+        SupplyChainErrors::InvalidLogistics
+    );
+    // Direct transfers have no carrier to pay
+    require!(
+        ctx.accounts.logistics.is_some() || shipping_cost == 0,
+        SupplyChainErrors::InvalidLogistics
+    );
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    // Units promised to seller orders can not be moved
+    from_stock.reserve(quantity)?;
+    let cost_basis = from_stock.dispatch(quantity)?;
+    from_stock.updated_at = now;
+    from_warehouse.product_count = from_warehouse
+        .product_count
+        .checked_sub(quantity)
+        .ok_or(SupplyChainErrors::InsufficientStock)?;
+    let volume = product
+        .unit_volume
+        .checked_mul(quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    from_warehouse.free(volume);
+    // Space at the destination is held for the whole trip
+    to_warehouse.occupy(volume)?;
+
+    if to_stock.created_at == 0 {
+        to_stock.warehouse_id = to_warehouse.warehouse_id;
+        to_stock.warehouse_pda = to_warehouse.key();
+        to_stock.product_id = product.product_id;
+        to_stock.product_pda = product.key();
+        to_stock.created_at = now;
+    }
+
+    stock_transfer.transfer_id = from_warehouse
+        .stock_transfer_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    stock_transfer.from_warehouse = from_warehouse.key();
+    stock_transfer.to_warehouse = to_warehouse.key();
+    stock_transfer.product_pda = product.key();
+    stock_transfer.quantity = quantity;
+    stock_transfer.cost_basis = cost_basis;
+    stock_transfer.created_at = now;
+    from_warehouse.stock_transfer_count = stock_transfer.transfer_id;
+
+    if let (Some(logistics), Some(shipment)) = (
+        ctx.accounts.logistics.as_mut(),
+        ctx.accounts.shipment.as_mut(),
+    ) {
+        require_keys_eq!(
+            ctx.accounts.fee_exemption.key(),
+            FeeExemption::address_for(&logistics.owner),
+            SupplyChainErrors::InvalidFeeExemption
+        );
+        let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+            0
+        } else {
+            ctx.accounts
+                .program_state
+                .purchase_fee_for(BalanceHolderKind::Logistics, shipping_cost)?
+        };
+        let logistics_amount = shipping_cost - platform_fee;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: signer.to_account_info(),
+                    to: logistics.to_account_info(),
+                },
+            ),
+            logistics_amount,
+        )?;
+        if platform_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: signer.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                platform_fee,
+            )?;
+        }
+
+        shipment.shipment_id = logistics.shipment_count + 1;
+        shipment.order_pda = stock_transfer.key();
+        shipment.provider_pda = logistics.key();
+        shipment.warehouse_pda = to_warehouse.key();
+        shipment.seller_pda = Pubkey::default();
+        shipment.product_id = product.product_id;
+        shipment.product_pda = product.key();
+        shipment.product_stock = quantity;
+        shipment.shipment_cost = shipping_cost;
+        shipment.status = ShipmentStatus::InTransit;
+        // The owner keeps custody until it hands the units to the carrier
+        shipment.custodian = signer.key();
+        shipment.checkpoint_count = 0;
+        shipment.shipment_started_at = now;
+        shipment.shipment_ended_at = 0;
+
+        logistics.balance += logistics_amount;
+        logistics.shipment_count += 1;
+        logistics.active_shipments += 1;
+        from_warehouse.logistic_count += 1;
+
+        stock_transfer.logistic_pda = logistics.key();
+        stock_transfer.shipment_pda = shipment.key();
+        stock_transfer.status = StockTransferStatus::InTransit;
+
+        emit_cpi!(ShipmentDispatched {
+            shipment: shipment.key(),
+            order: stock_transfer.key(),
+            provider: logistics.key(),
+            warehouse: to_warehouse.key(),
+            quantity,
+            shipment_cost: shipping_cost,
+            timestamp: now,
+        });
+        if platform_fee > 0 {
+            emit_cpi!(PlatformFeeCollected {
+                payer: signer.key(),
+                payee: logistics.key(),
+                amount: platform_fee,
+                timestamp: now,
+            });
+        }
+    } else {
+        to_stock.receive(quantity, cost_basis)?;
+        to_stock.updated_at = now;
+        to_warehouse.product_count = to_warehouse
+            .product_count
+            .checked_add(quantity)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        stock_transfer.status = StockTransferStatus::Completed;
+        stock_transfer.completed_at = now;
+    }
+
+    // Internal movements carry no price
+    transaction.transaction_id = user.transaction_count + 1;
+    transaction.from = from_warehouse.key();
+    transaction.to = to_warehouse.key();
+    transaction.amount = 0;
+    transaction.timestamp = now;
+    transaction.status = true;
+
+    user.transaction_count += 1;
+
+    emit_cpi!(WarehouseStockTransferred {
+        stock_transfer: stock_transfer.key(),
+        from_warehouse: from_warehouse.key(),
+        to_warehouse: to_warehouse.key(),
+        product: product.key(),
+        quantity,
+        shipment: stock_transfer.shipment_pda,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn receive_warehouse_stock_transfer(
+    ctx: Context<ReceiveWarehouseStockTransferCtx>,
+) -> Result<()> {
+    let stock_transfer = &mut ctx.accounts.stock_transfer;
+    let shipment = &mut ctx.accounts.shipment;
+    let logistics = &mut ctx.accounts.logistics;
+    let to_warehouse = &mut ctx.accounts.to_warehouse;
+    let to_stock = &mut ctx.accounts.to_stock;
+    let user = &ctx.accounts.user;
+
+    require!(
+        user.has_role(Role::Warehouse) && user.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        to_warehouse.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        stock_transfer.status == StockTransferStatus::InTransit,
+        SupplyChainErrors::StockTransferNotInTransit
+    );
+    require!(
+        shipment.status == ShipmentStatus::InTransit,
+        SupplyChainErrors::ShipmentNotInTransit
+    );
+    // The carrier must have handed the units over before they can be booked in
+    require!(
+        shipment.custodian == to_warehouse.owner,
+        SupplyChainErrors::InvalidCustodian
+    );
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    to_stock.receive(stock_transfer.quantity, stock_transfer.cost_basis)?;
+    to_stock.updated_at = now;
+    to_warehouse.product_count = to_warehouse
+        .product_count
+        .checked_add(stock_transfer.quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    shipment.status = ShipmentStatus::Delivered;
+    shipment.shipment_ended_at = now;
+    logistics.active_shipments = logistics.active_shipments.saturating_sub(1);
+
+    stock_transfer.status = StockTransferStatus::Completed;
+    stock_transfer.completed_at = now;
+
+    emit_cpi!(WarehouseStockTransferReceived {
+        stock_transfer: stock_transfer.key(),
+        to_warehouse: to_warehouse.key(),
+        quantity: stock_transfer.quantity,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// The owner can call a shipped transfer back while it is still in transit. The units and their
+// cost basis return to the source stock, the space held at the destination is released and the
+// carrier keeps the shipping cost it was paid
+pub fn cancel_warehouse_stock_transfer(
+    ctx: Context<CancelWarehouseStockTransferCtx>,
+) -> Result<()> {
+    let stock_transfer = &mut ctx.accounts.stock_transfer;
+    let shipment = &mut ctx.accounts.shipment;
+    let logistics = &mut ctx.accounts.logistics;
+    let from_warehouse = &mut ctx.accounts.from_warehouse;
+    let to_warehouse = &mut ctx.accounts.to_warehouse;
+    let from_stock = &mut ctx.accounts.from_stock;
+    let user = &ctx.accounts.user;
+
+    require!(
+        user.has_role(Role::Warehouse) && user.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        from_warehouse.owner == ctx.accounts.signer.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        stock_transfer.status == StockTransferStatus::InTransit,
+        SupplyChainErrors::StockTransferNotInTransit
+    );
+    require!(
+        shipment.status == ShipmentStatus::InTransit,
+        SupplyChainErrors::ShipmentNotInTransit
+    );
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let volume = ctx
+        .accounts
+        .product
+        .unit_volume
+        .checked_mul(stock_transfer.quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    from_warehouse.occupy(volume)?;
+    to_warehouse.free(volume);
+    from_stock.receive(stock_transfer.quantity, stock_transfer.cost_basis)?;
+    from_stock.updated_at = now;
+    from_warehouse.product_count = from_warehouse
+        .product_count
+        .checked_add(stock_transfer.quantity)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    shipment.status = ShipmentStatus::Returned;
+    shipment.shipment_ended_at = now;
+    logistics.active_shipments = logistics.active_shipments.saturating_sub(1);
+
+    stock_transfer.status = StockTransferStatus::Cancelled;
+    stock_transfer.completed_at = now;
+
+    emit_cpi!(WarehouseStockTransferCancelled {
+        stock_transfer: stock_transfer.key(),
+        from_warehouse: from_warehouse.key(),
+        quantity: stock_transfer.quantity,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferWarehouseStockCtx<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub user: Box<Account<'info, User>>,
+    #[account(mut, constraint = !from_warehouse.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub from_warehouse: Box<Account<'info, Warehouse>>,
+    #[account(mut, constraint = !to_warehouse.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub to_warehouse: Box<Account<'info, Warehouse>>,
+    pub product: Box<Account<'info, Product>>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", from_warehouse.key().as_ref(), product.key().as_ref()],
+        bump,
+    )]
+    pub from_stock: Box<Account<'info, WarehouseStock>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + WarehouseStock::INIT_SPACE,
+        seeds = [b"warehouse_stock", to_warehouse.key().as_ref(), product.key().as_ref()],
+        bump,
+    )]
+    pub to_stock: Box<Account<'info, WarehouseStock>>,
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + StockTransfer::INIT_SPACE,
+        seeds = [b"stock_transfer", from_warehouse.key().as_ref(), (from_warehouse.stock_transfer_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub stock_transfer: Box<Account<'info, StockTransfer>>,
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [b"transaction", user.key().as_ref(), (user.transaction_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub transaction: Box<Account<'info, Transaction>>,
+    // Carrier accounts below are only required when the units are shipped
+    #[account(mut, constraint = !logistics.frozen @ SupplyChainErrors::AccountFrozen)]
+    pub logistics: Option<Box<Account<'info, LogisticsProvider>>>,
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Shipment::INIT_SPACE,
+        seeds = [b"shipment", stock_transfer.key().as_ref()],
+        bump,
+    )]
+    pub shipment: Option<Box<Account<'info, Shipment>>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    /// CHECK: Exemption PDA of the carrier owner, derived in the handler since the carrier is
+    /// optional and ignored for direct transfers
+    pub fee_exemption: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReceiveWarehouseStockTransferCtx<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub user: Box<Account<'info, User>>,
+    #[account(mut)]
+    pub stock_transfer: Box<Account<'info, StockTransfer>>,
+    #[account(mut, address = stock_transfer.shipment_pda)]
+    pub shipment: Box<Account<'info, Shipment>>,
+    #[account(mut, address = shipment.provider_pda)]
+    pub logistics: Box<Account<'info, LogisticsProvider>>,
+    #[account(
+        mut,
+        address = stock_transfer.to_warehouse,
+        constraint = !to_warehouse.frozen @ SupplyChainErrors::AccountFrozen,
+    )]
+    pub to_warehouse: Box<Account<'info, Warehouse>>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", to_warehouse.key().as_ref(), stock_transfer.product_pda.as_ref()],
+        bump,
+    )]
+    pub to_stock: Box<Account<'info, WarehouseStock>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelWarehouseStockTransferCtx<'info> {
+    pub signer: Signer<'info>,
+    pub user: Box<Account<'info, User>>,
+    #[account(mut)]
+    pub stock_transfer: Box<Account<'info, StockTransfer>>,
+    #[account(mut, address = stock_transfer.shipment_pda)]
+    pub shipment: Box<Account<'info, Shipment>>,
+    #[account(mut, address = shipment.provider_pda)]
+    pub logistics: Box<Account<'info, LogisticsProvider>>,
+    #[account(
+        mut,
+        address = stock_transfer.from_warehouse,
+        constraint = !from_warehouse.frozen @ SupplyChainErrors::AccountFrozen,
+    )]
+    pub from_warehouse: Box<Account<'info, Warehouse>>,
+    #[account(mut, address = stock_transfer.to_warehouse)]
+    pub to_warehouse: Box<Account<'info, Warehouse>>,
+    #[account(address = stock_transfer.product_pda)]
+    pub product: Box<Account<'info, Product>>,
+    #[account(
+        mut,
+        seeds = [b"warehouse_stock", from_warehouse.key().as_ref(), stock_transfer.product_pda.as_ref()],
+        bump,
+    )]
+    pub from_stock: Box<Account<'info, WarehouseStock>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+}
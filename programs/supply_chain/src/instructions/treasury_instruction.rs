@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::errors::SupplyChainErrors;
+use crate::events::TreasuryWithdrawn;
+use crate::instructions::token_vault_instruction::transfer_from_vault;
+use crate::states::{program_state::ProgramState, treasury::Treasury};
+
+pub fn withdraw_treasury(ctx: Context<WithdrawTreasuryCtx>, amount: u64) -> Result<()> {
+    require!(
+        ctx.accounts.program_state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    let treasury = &mut ctx.accounts.treasury;
+    let treasury_info = treasury.to_account_info();
+    // The treasury must stay rent exempt, only the collected fees above that are withdrawable
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_info.data_len());
+    let available = treasury_info.lamports().saturating_sub(rent_exempt_minimum);
+    require!(amount <= available, SupplyChainErrors::InsufficientBalance);
+
+    **treasury_info.try_borrow_mut_lamports()? -= amount;
+    **ctx
+        .accounts
+        .owner
+        .to_account_info()
+        .try_borrow_mut_lamports()? += amount;
+    treasury.lamports_withdrawn = treasury
+        .lamports_withdrawn
+        .checked_add(amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(TreasuryWithdrawn {
+        recipient: ctx.accounts.owner.key(),
+        mint: Pubkey::default(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+pub fn withdraw_treasury_tokens(
+    ctx: Context<WithdrawTreasuryTokensCtx>,
+    amount: u64,
+) -> Result<()> {
+    require!(
+        ctx.accounts.program_state.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        amount <= ctx.accounts.treasury_vault.amount,
+        SupplyChainErrors::InsufficientBalance
+    );
+
+    transfer_from_vault(
+        &ctx.accounts.treasury_vault,
+        &ctx.accounts.destination_token_account,
+        &ctx.accounts.payment_mint,
+        &ctx.accounts.vault_authority,
+        ctx.bumps.vault_authority,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.tokens_withdrawn = treasury
+        .tokens_withdrawn
+        .checked_add(amount)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(TreasuryWithdrawn {
+        recipient: ctx.accounts.destination_token_account.key(),
+        mint: ctx.accounts.payment_mint.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawTreasuryCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawTreasuryTokensCtx<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(
+        mut,
+        seeds = [b"vault", treasury.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+    )]
+    pub treasury_vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut, token::mint = payment_mint)]
+    pub destination_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [b"program_state"], bump)]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    #[account(address = program_state.payment_mint @ SupplyChainErrors::InvalidPaymentMint)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::ANCHOR_DISCRIMINATOR_SIZE,
+    errors::SupplyChainErrors,
+    events::ProductUpdated,
+    states::{factory::Factory, price_history::PriceHistory, product::Product},
+};
+
+pub fn update_product(
+    ctx: Context<UpdateProductCtx>,
+    product_name: Option<String>,
+    product_description: Option<String>,
+    product_image: Option<String>,
+    product_price: Option<u64>,
+    mrp: Option<u64>,
+) -> Result<()> {
+    let product = &mut ctx.accounts.product;
+    let price_history = &mut ctx.accounts.price_history;
+    require!(
+        ctx.accounts.factory.owner == ctx.accounts.owner.key(),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+
+    if let Some(product_name) = product_name {
+        require!(product_name.len() <= 32, SupplyChainErrors::InvalidName);
+        product.product_name = product_name;
+    }
+    if let Some(product_description) = product_description {
+        require!(
+            product_description.len() <= 512,
+            SupplyChainErrors::InvalidDescription
+        );
+        product.product_description = product_description;
+    }
+    if let Some(product_image) = product_image {
+        require!(
+            product_image.len() <= 132,
+            SupplyChainErrors::InvalidProductImage
+        );
+        product.product_image = product_image;
+    }
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    // Seed a fresh history with the price set at creation so the first change has a baseline
+    if price_history.count == 0 {
+        price_history.product_pda = product.key();
+        price_history.record(product.product_price, product.mrp, product.created_at);
+    }
+    let new_price = product_price.unwrap_or(product.product_price);
+    let new_mrp = mrp.unwrap_or(product.mrp);
+    if new_price != product.product_price || new_mrp != product.mrp {
+        product.product_price = new_price;
+        product.mrp = new_mrp;
+        price_history.record(new_price, new_mrp, now);
+    }
+
+    emit_cpi!(ProductUpdated {
+        product: product.key(),
+        product_price: product.product_price,
+        mrp: product.mrp,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateProductCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub product: Account<'info, Product>,
+    #[account(address = product.factory_pda @ SupplyChainErrors::InvalidFactory)]
+    pub factory: Account<'info, Factory>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + PriceHistory::INIT_SPACE,
+        seeds = [b"price_history", product.key().as_ref()],
+        bump,
+    )]
+    pub price_history: Box<Account<'info, PriceHistory>>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::errors::SupplyChainErrors;
+use crate::events::BalanceWithdrawn;
+use crate::states::{
+    balance_holder::BalanceHolderKind, fee_exemption::FeeExemption, program_state::ProgramState,
+    transaction::Transaction, treasury::Treasury, user::User,
+};
+
+pub fn withdraw(ctx: Context<WithdrawCtx>, kind: BalanceHolderKind, amount: u64) -> Result<()> {
+    let user = &mut ctx.accounts.user;
+    let transaction = &mut ctx.accounts.transaction;
+    let program_state = &ctx.accounts.program_state;
+    let holder = ctx.accounts.holder.to_account_info();
+    let owner = ctx.accounts.owner.key();
+
+    require!(
+        user.has_role(kind.role()),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        amount >= program_state.min_withdraw_amount,
+        SupplyChainErrors::InsifficentWithdraw
+    );
+    kind.debit(&holder, owner, amount)?;
+
+    // The holder account must stay rent exempt after the lamports leave it
+    let rent_balance = Rent::get()?.minimum_balance(holder.data_len());
+    require!(
+        amount <= holder.lamports().saturating_sub(rent_balance),
+        SupplyChainErrors::InsufficientBalance
+    );
+
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        program_state.withdraw_fee_for(kind, amount)?
+    };
+    let creator_amount = amount
+        .checked_sub(platform_fee)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    **holder.try_borrow_mut_lamports()? -= amount;
+    **ctx
+        .accounts
+        .owner
+        .to_account_info()
+        .try_borrow_mut_lamports()? += creator_amount;
+    **ctx
+        .accounts
+        .treasury
+        .to_account_info()
+        .try_borrow_mut_lamports()? += platform_fee;
+
+    transaction.transaction_id = user
+        .transaction_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    transaction.amount = amount;
+    transaction.from = holder.key();
+    transaction.to = owner;
+    transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+    transaction.status = true;
+
+    user.transaction_count = user
+        .transaction_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(BalanceWithdrawn {
+        holder: holder.key(),
+        owner,
+        amount,
+        platform_fee,
+        timestamp: transaction.timestamp,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump,
+    )]
+    pub user: Account<'info, User>,
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [b"transaction", user.key().as_ref(), (user.transaction_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub transaction: Account<'info, Transaction>,
+    /// CHECK: Deserialized according to the balance holder kind in the handler
+    #[account(mut, owner = crate::ID)]
+    pub holder: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", owner.key().as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Account<'info, Treasury>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::ANCHOR_DISCRIMINATOR_SIZE;
+use crate::errors::SupplyChainErrors;
+use crate::events::BalanceWithdrawn;
+use crate::instructions::token_vault_instruction::transfer_from_vault;
+use crate::states::{
+    balance_holder::BalanceHolderKind, fee_exemption::FeeExemption, program_state::ProgramState,
+    transaction::Transaction, treasury::Treasury, user::User,
+};
+
+pub fn withdraw_token_balance(
+    ctx: Context<WithdrawTokenBalanceCtx>,
+    kind: BalanceHolderKind,
+    amount: u64,
+) -> Result<()> {
+    let user = &mut ctx.accounts.user;
+    let transaction = &mut ctx.accounts.transaction;
+    let program_state = &ctx.accounts.program_state;
+    let vault = &ctx.accounts.vault;
+    let owner = ctx.accounts.owner.key();
+
+    require!(
+        user.has_role(kind.role()),
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        kind.owner_of(&ctx.accounts.holder)? == owner,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(
+        !kind.frozen_of(&ctx.accounts.holder)?,
+        SupplyChainErrors::AccountFrozen
+    );
+    require!(
+        amount > 0 && amount <= vault.amount,
+        SupplyChainErrors::InsufficientBalance
+    );
+
+    let platform_fee = if FeeExemption::is_active(&ctx.accounts.fee_exemption) {
+        0
+    } else {
+        program_state.withdraw_fee_for(kind, amount)?
+    };
+    let creator_amount = amount
+        .checked_sub(platform_fee)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    transfer_from_vault(
+        vault,
+        &ctx.accounts.destination_token_account,
+        &ctx.accounts.payment_mint,
+        &ctx.accounts.vault_authority,
+        ctx.bumps.vault_authority,
+        &ctx.accounts.token_program,
+        creator_amount,
+    )?;
+    if platform_fee > 0 {
+        transfer_from_vault(
+            vault,
+            &ctx.accounts.treasury_vault,
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.vault_authority,
+            ctx.bumps.vault_authority,
+            &ctx.accounts.token_program,
+            platform_fee,
+        )?;
+    }
+
+    transaction.transaction_id = user
+        .transaction_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+    transaction.amount = amount;
+    transaction.from = vault.key();
+    transaction.to = ctx.accounts.destination_token_account.key();
+    transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+    transaction.status = true;
+
+    user.transaction_count = user
+        .transaction_count
+        .checked_add(1)
+        .ok_or(SupplyChainErrors::Overflow)?;
+
+    emit_cpi!(BalanceWithdrawn {
+        holder: ctx.accounts.holder.key(),
+        owner,
+        amount,
+        platform_fee,
+        timestamp: transaction.timestamp,
+    });
+    Ok(())
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawTokenBalanceCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump,
+    )]
+    pub user: Box<Account<'info, User>>,
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [b"transaction", user.key().as_ref(), (user.transaction_count + 1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub transaction: Box<Account<'info, Transaction>>,
+    /// CHECK: Deserialized according to the balance holder kind in the handler
+    pub holder: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", holder.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: PDA that signs for every token vault
+    #[account(seeds = [b"vault_authority"], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = owner,
+    )]
+    pub destination_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [b"treasury"], bump)]
+    pub treasury: Box<Account<'info, Treasury>>,
+    #[account(
+        mut,
+        seeds = [b"vault", treasury.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+    )]
+    pub treasury_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"program_state"],
+        bump,
+        constraint = !program_state.paused @ SupplyChainErrors::ProgramPaused,
+    )]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    /// CHECK: Exemption PDA of the payee, see FeeExemption::is_active
+    #[account(seeds = [b"fee_exemption", owner.key().as_ref()], bump)]
+    pub fee_exemption: UncheckedAccount<'info>,
+    #[account(address = program_state.payment_mint @ SupplyChainErrors::InvalidPaymentMint)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
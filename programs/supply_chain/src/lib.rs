@@ -1,11 +1,21 @@
+#![allow(clippy::too_many_arguments)]
+// The #[program] macro checks cfgs (custom-heap, target_os = "solana") this crate never declares
+#![allow(unexpected_cfgs)]
+
 use anchor_lang::prelude::*;
 
 mod constants;
 mod errors;
+mod events;
 mod instructions;
 mod states;
 
 use crate::instructions::*;
+use crate::states::{
+    balance_holder::BalanceHolderKind,
+    inspection::{InspectionOutcome, InspectionScores},
+    user::Role,
+};
 
 declare_id!("BvjL3nMVHZowbJdYAyan4Xn6MXunbBAYQC9tXN2EAQZf");
 
@@ -17,19 +27,63 @@ pub mod supply_chain {
         instructions::initialize_program_state(ctx)
     }
 
-    pub fn update_platform_fee(ctx: Context<UpdatePlatformFeeCtx>, fee: u64) -> Result<()> {
-        instructions::update_platform_fee(ctx, fee)
+    pub fn update_platform_fee(
+        ctx: Context<UpdatePlatformFeeCtx>,
+        kind: BalanceHolderKind,
+        withdraw_fee_bps: u16,
+        purchase_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::update_platform_fee(ctx, kind, withdraw_fee_bps, purchase_fee_bps)
+    }
+
+    pub fn propose_owner(ctx: Context<ProposeOwnerCtx>, new_owner: Pubkey) -> Result<()> {
+        instructions::propose_owner(ctx, new_owner)
+    }
+
+    pub fn accept_ownership(ctx: Context<AcceptOwnershipCtx>) -> Result<()> {
+        instructions::accept_ownership(ctx)
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasuryCtx>, amount: u64) -> Result<()> {
+        instructions::withdraw_treasury(ctx, amount)
+    }
+
+    pub fn withdraw_treasury_tokens(
+        ctx: Context<WithdrawTreasuryTokensCtx>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_treasury_tokens(ctx, amount)
+    }
+
+    pub fn set_program_paused(ctx: Context<SetProgramPausedCtx>, paused: bool) -> Result<()> {
+        instructions::set_program_paused(ctx, paused)
+    }
+
+    pub fn set_account_frozen(
+        ctx: Context<SetAccountFrozenCtx>,
+        kind: BalanceHolderKind,
+        frozen: bool,
+    ) -> Result<()> {
+        instructions::set_account_frozen(ctx, kind, frozen)
     }
 
     pub fn create_user(
         ctx: Context<CreateUserInstructionContext>,
         name: String,
         email: String,
-        role: String,
+        role: Role,
     ) -> Result<()> {
         instructions::create_user(ctx, name, email, role)
     }
 
+    pub fn add_user_role(ctx: Context<UpdateUserRoleCtx>, role: Role) -> Result<()> {
+        instructions::add_user_role(ctx, role)
+    }
+
+    pub fn remove_user_role(ctx: Context<UpdateUserRoleCtx>, role: Role) -> Result<()> {
+        instructions::remove_user_role(ctx, role)
+    }
+
     pub fn create_factory(
         ctx: Context<CreateFactoryInstructionContext>,
         name: String,
@@ -51,6 +105,7 @@ pub mod supply_chain {
         raw_material_used: u64,
         product_stock: u64,
         mrp: u64,
+        unit_volume: u64,
     ) -> Result<()> {
         instructions::create_product(
             ctx,
@@ -62,44 +117,172 @@ pub mod supply_chain {
             raw_material_used,
             product_stock,
             mrp,
+            unit_volume,
         )
     }
 
-    pub fn inspect_product_instruction(
-        ctx: Context<InspectProductContext>,
+    pub fn update_product(
+        ctx: Context<UpdateProductCtx>,
+        product_name: Option<String>,
+        product_description: Option<String>,
+        product_image: Option<String>,
+        product_price: Option<u64>,
+        mrp: Option<u64>,
+    ) -> Result<()> {
+        instructions::update_product(
+            ctx,
+            product_name,
+            product_description,
+            product_image,
+            product_price,
+            mrp,
+        )
+    }
+
+    pub fn restock_product(
+        ctx: Context<RestockProductCtx>,
+        batch_number: String,
+        quantity: u64,
+        raw_material_used: u64,
+        produced_at: u64,
+        expires_at: u64,
+    ) -> Result<()> {
+        instructions::restock_product(
+            ctx,
+            batch_number,
+            quantity,
+            raw_material_used,
+            produced_at,
+            expires_at,
+        )
+    }
+
+    pub fn inspect_batch(ctx: Context<InspectBatchCtx>, outcome: InspectionOutcome) -> Result<()> {
+        instructions::inspect_batch(ctx, outcome)
+    }
+
+    pub fn recall_product(ctx: Context<RecallProductCtx>) -> Result<()> {
+        instructions::recall_product(ctx)
+    }
+
+    pub fn fund_recall(ctx: Context<FundRecallCtx>, amount: u64) -> Result<()> {
+        instructions::fund_recall(ctx, amount)
+    }
+
+    pub fn claim_recall_refund_as_customer(
+        ctx: Context<ClaimRecallRefundAsCustomerCtx>,
+    ) -> Result<()> {
+        instructions::claim_recall_refund_as_customer(ctx)
+    }
+
+    pub fn claim_recall_refund_as_seller(ctx: Context<ClaimRecallRefundAsSellerCtx>) -> Result<()> {
+        instructions::claim_recall_refund_as_seller(ctx)
+    }
+
+    pub fn register_inspector(
+        ctx: Context<RegisterInspectorCtx>,
         name: String,
         latitude: f64,
         longitude: f64,
+        fee_charge_per_product: u64,
+    ) -> Result<()> {
+        instructions::register_inspector(ctx, name, latitude, longitude, fee_charge_per_product)
+    }
+
+    pub fn update_inspector_fee(
+        ctx: Context<UpdateInspectorFeeCtx>,
+        fee_charge_per_product: u64,
+    ) -> Result<()> {
+        instructions::update_inspector_fee(ctx, fee_charge_per_product)
+    }
+
+    pub fn stake_inspector(ctx: Context<StakeInspectorCtx>, amount: u64) -> Result<()> {
+        instructions::stake_inspector(ctx, amount)
+    }
+
+    pub fn unstake_inspector(ctx: Context<UnstakeInspectorCtx>, amount: u64) -> Result<()> {
+        instructions::unstake_inspector(ctx, amount)
+    }
+
+    pub fn slash_inspector_stake(ctx: Context<SlashInspectorStakeCtx>, amount: u64) -> Result<()> {
+        instructions::slash_inspector_stake(ctx, amount)
+    }
+
+    pub fn request_inspection(ctx: Context<RequestInspectionCtx>, fee: u64) -> Result<()> {
+        instructions::request_inspection(ctx, fee)
+    }
+
+    pub fn accept_inspection_request(ctx: Context<AcceptInspectionRequestCtx>) -> Result<()> {
+        instructions::accept_inspection_request(ctx)
+    }
+
+    pub fn cancel_inspection_request(ctx: Context<CancelInspectionRequestCtx>) -> Result<()> {
+        instructions::cancel_inspection_request(ctx)
+    }
+
+    pub fn dispute_inspection(
+        ctx: Context<DisputeInspectionCtx>,
+        reason: String,
+        review_fee: u64,
+    ) -> Result<()> {
+        instructions::dispute_inspection(ctx, reason, review_fee)
+    }
+
+    pub fn assign_dispute_reviewer(ctx: Context<AssignDisputeReviewerCtx>) -> Result<()> {
+        instructions::assign_dispute_reviewer(ctx)
+    }
+
+    pub fn resolve_inspection_dispute(
+        ctx: Context<ResolveInspectionDisputeCtx>,
+        outcome: InspectionOutcome,
+        scores: InspectionScores,
+        notes: String,
+    ) -> Result<()> {
+        instructions::resolve_inspection_dispute(ctx, outcome, scores, notes)
+    }
+
+    pub fn cancel_inspection_dispute(ctx: Context<CancelInspectionDisputeCtx>) -> Result<()> {
+        instructions::cancel_inspection_dispute(ctx)
+    }
+
+    pub fn expire_inspection_dispute(ctx: Context<ExpireInspectionDisputeCtx>) -> Result<()> {
+        instructions::expire_inspection_dispute(ctx)
+    }
+
+    pub fn inspect_product_instruction(
+        ctx: Context<InspectProductContext>,
         product_id: u64,
-        inspection_outcome: String,
+        outcome: InspectionOutcome,
+        scores: InspectionScores,
         notes: String,
-        fee_charge_per_product: u64,
+        valid_until: u64,
     ) -> Result<()> {
         instructions::inspect_product_instruction(
             ctx,
-            name,
-            latitude,
-            longitude,
             product_id,
-            inspection_outcome,
+            outcome,
+            scores,
             notes,
-            fee_charge_per_product,
+            valid_until,
         )
     }
 
-    pub fn pay_product_inspector_instruction(
-        ctx: Context<PayProductInspectorInstruction>,
-        inspector_id: u64,
+    pub fn reinspect_product_instruction(
+        ctx: Context<InspectProductContext>,
         product_id: u64,
+        outcome: InspectionOutcome,
+        scores: InspectionScores,
+        notes: String,
+        valid_until: u64,
     ) -> Result<()> {
-        instructions::pay_product_inspector_instruction(ctx, inspector_id, product_id)
-    }
-
-    pub fn withdraw_inspector_balance(
-        ctx: Context<WithdrawInspectorBalanceCtx>,
-        amount: u64,
-    ) -> Result<()> {
-        instructions::withdraw_inspector_balance(ctx, amount)
+        instructions::reinspect_product_instruction(
+            ctx,
+            product_id,
+            outcome,
+            scores,
+            notes,
+            valid_until,
+        )
     }
 
     pub fn create_warehouse_instrution(
@@ -124,20 +307,72 @@ pub mod supply_chain {
         )
     }
 
-    pub fn withdraw_balance_as_factory(
-        ctx: Context<WithdrawBalanceAsFactoryCtx>,
-        amount: u64,
+    pub fn resize_warehouse_instruction(
+        ctx: Context<ResizeWarehouseCtx>,
+        warehouse_size: u64,
     ) -> Result<()> {
-        instructions::withdraw_balance_as_factory(ctx, amount)
+        instructions::resize_warehouse_instruction(ctx, warehouse_size)
     }
 
-    pub fn buy_product_as_warehouse(
-        ctx: Context<BuyProductAsWarehouseCtx>,
+    pub fn place_purchase_order(
+        ctx: Context<PlacePurchaseOrderCtx>,
         product_id: u64,
         factory_id: u64,
-        stock_to_purchase: u64,
+        quantity: u64,
+    ) -> Result<()> {
+        instructions::place_purchase_order(ctx, product_id, factory_id, quantity)
+    }
+
+    pub fn accept_purchase_order(ctx: Context<AcceptPurchaseOrderCtx>) -> Result<()> {
+        instructions::accept_purchase_order(ctx)
+    }
+
+    pub fn reject_purchase_order(ctx: Context<ClosePurchaseOrderCtx>) -> Result<()> {
+        instructions::reject_purchase_order(ctx)
+    }
+
+    pub fn cancel_purchase_order(ctx: Context<ClosePurchaseOrderCtx>) -> Result<()> {
+        instructions::cancel_purchase_order(ctx)
+    }
+
+    pub fn refund_purchase_order(ctx: Context<ClosePurchaseOrderCtx>) -> Result<()> {
+        instructions::refund_purchase_order(ctx)
+    }
+
+    pub fn dispute_purchase_order(ctx: Context<DisputePurchaseOrderCtx>) -> Result<()> {
+        instructions::dispute_purchase_order(ctx)
+    }
+
+    pub fn dispatch_purchase_order(
+        ctx: Context<DispatchPurchaseOrderCtx>,
+        logistics_id: u64,
+        shipping_cost: u64,
+    ) -> Result<()> {
+        instructions::dispatch_purchase_order(ctx, logistics_id, shipping_cost)
+    }
+
+    pub fn receive_purchase_order(ctx: Context<ReceivePurchaseOrderCtx>) -> Result<()> {
+        instructions::receive_purchase_order(ctx)
+    }
+
+    pub fn transfer_warehouse_stock(
+        ctx: Context<TransferWarehouseStockCtx>,
+        quantity: u64,
+        shipping_cost: u64,
     ) -> Result<()> {
-        instructions::buy_product_as_warehouse(ctx, product_id, factory_id, stock_to_purchase)
+        instructions::transfer_warehouse_stock(ctx, quantity, shipping_cost)
+    }
+
+    pub fn receive_warehouse_stock_transfer(
+        ctx: Context<ReceiveWarehouseStockTransferCtx>,
+    ) -> Result<()> {
+        instructions::receive_warehouse_stock_transfer(ctx)
+    }
+
+    pub fn cancel_warehouse_stock_transfer(
+        ctx: Context<CancelWarehouseStockTransferCtx>,
+    ) -> Result<()> {
+        instructions::cancel_warehouse_stock_transfer(ctx)
     }
 
     pub fn create_seller_instruction(
@@ -157,12 +392,6 @@ pub mod supply_chain {
             contact_info,
         )
     }
-    pub fn withdraw_balance_as_warehouse_instruction(
-        ctx: Context<WithdrawBalanceAsWarehouseCtx>,
-        amount: u64,
-    ) -> Result<()> {
-        instructions::withdraw_balance_as_warehouse_instruction(ctx, amount)
-    }
 
     pub fn create_order_instruction_as_seller(
         ctx: Context<CreateOrderAsSellerCtx>,
@@ -177,13 +406,38 @@ pub mod supply_chain {
             product_stock,
         )
     }
+
+    pub fn cancel_order_instruction_as_seller(ctx: Context<CancelOrderAsSellerCtx>) -> Result<()> {
+        instructions::cancel_order_instruction_as_seller(ctx)
+    }
+
+    pub fn accept_order_instruction_as_warehouse(
+        ctx: Context<ReviewOrderAsWarehouseCtx>,
+    ) -> Result<()> {
+        instructions::accept_order_instruction_as_warehouse(ctx)
+    }
+
+    pub fn reject_order_instruction_as_warehouse(
+        ctx: Context<ReviewOrderAsWarehouseCtx>,
+    ) -> Result<()> {
+        instructions::reject_order_instruction_as_warehouse(ctx)
+    }
+
+    pub fn dispute_order_instruction_as_seller(
+        ctx: Context<DisputeOrderAsSellerCtx>,
+    ) -> Result<()> {
+        instructions::dispute_order_instruction_as_seller(ctx)
+    }
+
+    pub fn refund_order_instruction(ctx: Context<RefundOrderCtx>) -> Result<()> {
+        instructions::refund_order_instruction(ctx)
+    }
+
     pub fn create_logistics_instruction(
         ctx: Context<CreateLogisticsContext>,
         name: String,
         transportation_mode: String,
         contact_info: String,
-        product_id: u64,
-        warehouse_id: u64,
         latitude: f64,
         longitude: f64,
     ) -> Result<()> {
@@ -192,8 +446,6 @@ pub mod supply_chain {
             name,
             transportation_mode,
             contact_info,
-            product_id,
-            warehouse_id,
             latitude,
             longitude,
         )
@@ -215,30 +467,75 @@ pub mod supply_chain {
         )
     }
 
+    pub fn record_checkpoint(
+        ctx: Context<RecordCheckpointCtx>,
+        location: String,
+        latitude: f64,
+        longitude: f64,
+        condition: String,
+    ) -> Result<()> {
+        instructions::record_checkpoint(ctx, location, latitude, longitude, condition)
+    }
+
+    pub fn handoff_custody(
+        ctx: Context<HandoffCustodyCtx>,
+        location: String,
+        latitude: f64,
+        longitude: f64,
+        condition: String,
+    ) -> Result<()> {
+        instructions::handoff_custody(ctx, location, latitude, longitude, condition)
+    }
+
     pub fn receive_product_instruction_as_seller(
         ctx: Context<ReceiveProductAsSellerCtx>,
     ) -> Result<()> {
         instructions::receive_product_instruction_as_seller(ctx)
     }
 
-    pub fn withdraw_balance_as_logistics_instruction(
-        ctx: Context<WithdrawBalanceAsLogisticCtx>,
+    pub fn buy_product_as_customer_ctx(
+        ctx: Context<BuyProductAsCustomerCtx>,
+        stock: u64,
+    ) -> Result<()> {
+        instructions::buy_product_as_customer_ctx(ctx, stock)
+    }
+
+    pub fn set_payment_mint(ctx: Context<SetPaymentMintCtx>) -> Result<()> {
+        instructions::set_payment_mint(ctx)
+    }
+
+    pub fn create_token_vault(ctx: Context<CreateTokenVaultCtx>) -> Result<()> {
+        instructions::create_token_vault(ctx)
+    }
+
+    pub fn withdraw_token_balance(
+        ctx: Context<WithdrawTokenBalanceCtx>,
+        kind: BalanceHolderKind,
         amount: u64,
     ) -> Result<()> {
-        instructions::withdraw_balance_as_logistics_instruction(ctx, amount)
+        instructions::withdraw_token_balance(ctx, kind, amount)
     }
 
-    pub fn withdraw_balance_as_seller_instruction(
-        ctx: Context<WithdrawBalanceAsSellerCtx>,
+    pub fn withdraw(
+        ctx: Context<WithdrawCtx>,
+        kind: BalanceHolderKind,
         amount: u64,
     ) -> Result<()> {
-        instructions::withdraw_balance_as_seller_instruction(ctx, amount)
+        instructions::withdraw(ctx, kind, amount)
     }
 
-    pub fn buy_product_as_customer_ctx(
-        ctx: Context<BuyProductAsCustomerCtx>,
-        stock: u64,
+    pub fn update_min_withdraw_amount(
+        ctx: Context<UpdateMinWithdrawAmountCtx>,
+        amount: u64,
     ) -> Result<()> {
-        instructions::buy_product_as_customer_ctx(ctx, stock)
+        instructions::update_min_withdraw_amount(ctx, amount)
+    }
+
+    pub fn add_fee_exemption(ctx: Context<AddFeeExemptionCtx>) -> Result<()> {
+        instructions::add_fee_exemption(ctx)
+    }
+
+    pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemptionCtx>) -> Result<()> {
+        instructions::remove_fee_exemption(ctx)
     }
 }
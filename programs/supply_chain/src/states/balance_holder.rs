@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+use crate::states::{
+    factory::Factory, inspector_profile::InspectorProfile, logistics_provider::LogisticsProvider,
+    seller::Seller, user::Role, warehouse::Warehouse,
+};
+
+// Common view over every account that collects payments and tracks a withdrawable balance
+pub trait BalanceHolder {
+    fn owner(&self) -> Pubkey;
+    fn balance(&self) -> u64;
+    fn set_balance(&mut self, balance: u64);
+    fn frozen(&self) -> bool;
+    fn set_frozen(&mut self, frozen: bool);
+}
+
+macro_rules! impl_balance_holder {
+    ($($account:ty),*) => {
+        $(
+            impl BalanceHolder for $account {
+                fn owner(&self) -> Pubkey {
+                    self.owner
+                }
+
+                fn balance(&self) -> u64 {
+                    self.balance
+                }
+
+                fn set_balance(&mut self, balance: u64) {
+                    self.balance = balance;
+                }
+
+                fn frozen(&self) -> bool {
+                    self.frozen
+                }
+
+                fn set_frozen(&mut self, frozen: bool) {
+                    self.frozen = frozen;
+                }
+            }
+        )*
+    };
+}
+
+impl_balance_holder!(
+    Factory,
+    Warehouse,
+    Seller,
+    InspectorProfile,
+    LogisticsProvider
+);
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BalanceHolderKind {
+    Factory,
+    Warehouse,
+    Seller,
+    Inspector,
+    Logistics,
+}
+
+impl BalanceHolderKind {
+    pub fn role(self) -> Role {
+        match self {
+            BalanceHolderKind::Factory => Role::Factory,
+            BalanceHolderKind::Warehouse => Role::Warehouse,
+            BalanceHolderKind::Seller => Role::Seller,
+            BalanceHolderKind::Inspector => Role::Inspector,
+            BalanceHolderKind::Logistics => Role::Logistics,
+        }
+    }
+
+    pub fn owner_of(self, holder: &AccountInfo) -> Result<Pubkey> {
+        let owner = match self {
+            BalanceHolderKind::Factory => load::<Factory>(holder)?.owner(),
+            BalanceHolderKind::Warehouse => load::<Warehouse>(holder)?.owner(),
+            BalanceHolderKind::Seller => load::<Seller>(holder)?.owner(),
+            BalanceHolderKind::Inspector => load::<InspectorProfile>(holder)?.owner(),
+            BalanceHolderKind::Logistics => load::<LogisticsProvider>(holder)?.owner(),
+        };
+        Ok(owner)
+    }
+
+    pub fn frozen_of(self, holder: &AccountInfo) -> Result<bool> {
+        let frozen = match self {
+            BalanceHolderKind::Factory => load::<Factory>(holder)?.frozen(),
+            BalanceHolderKind::Warehouse => load::<Warehouse>(holder)?.frozen(),
+            BalanceHolderKind::Seller => load::<Seller>(holder)?.frozen(),
+            BalanceHolderKind::Inspector => load::<InspectorProfile>(holder)?.frozen(),
+            BalanceHolderKind::Logistics => load::<LogisticsProvider>(holder)?.frozen(),
+        };
+        Ok(frozen)
+    }
+
+    // Checks ownership and subtracts the amount from the tracked balance of the holder
+    pub fn debit(self, holder: &AccountInfo, owner: Pubkey, amount: u64) -> Result<()> {
+        match self {
+            BalanceHolderKind::Factory => debit::<Factory>(holder, owner, amount),
+            BalanceHolderKind::Warehouse => debit::<Warehouse>(holder, owner, amount),
+            BalanceHolderKind::Seller => debit::<Seller>(holder, owner, amount),
+            BalanceHolderKind::Inspector => debit::<InspectorProfile>(holder, owner, amount),
+            BalanceHolderKind::Logistics => debit::<LogisticsProvider>(holder, owner, amount),
+        }
+    }
+
+    pub fn set_frozen(self, holder: &AccountInfo, frozen: bool) -> Result<()> {
+        match self {
+            BalanceHolderKind::Factory => set_frozen::<Factory>(holder, frozen),
+            BalanceHolderKind::Warehouse => set_frozen::<Warehouse>(holder, frozen),
+            BalanceHolderKind::Seller => set_frozen::<Seller>(holder, frozen),
+            BalanceHolderKind::Inspector => set_frozen::<InspectorProfile>(holder, frozen),
+            BalanceHolderKind::Logistics => set_frozen::<LogisticsProvider>(holder, frozen),
+        }
+    }
+}
+
+// The discriminator check rejects accounts of any other kind
+fn load<T: AccountDeserialize>(holder: &AccountInfo) -> Result<T> {
+    require_keys_eq!(
+        *holder.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    let data = holder.try_borrow_data()?;
+    T::try_deserialize(&mut &data[..])
+}
+
+fn debit<T>(holder: &AccountInfo, owner: Pubkey, amount: u64) -> Result<()>
+where
+    T: BalanceHolder + AccountDeserialize + AccountSerialize,
+{
+    let mut account = load::<T>(holder)?;
+    require_keys_eq!(
+        account.owner(),
+        owner,
+        SupplyChainErrors::UnauthorizedAccess
+    );
+    require!(!account.frozen(), SupplyChainErrors::AccountFrozen);
+    let balance = account
+        .balance()
+        .checked_sub(amount)
+        .ok_or(SupplyChainErrors::InsufficientBalance)?;
+    account.set_balance(balance);
+    let mut data = holder.try_borrow_mut_data()?;
+    account.try_serialize(&mut &mut data[..])
+}
+
+fn set_frozen<T>(holder: &AccountInfo, frozen: bool) -> Result<()>
+where
+    T: BalanceHolder + AccountDeserialize + AccountSerialize,
+{
+    let mut account = load::<T>(holder)?;
+    account.set_frozen(frozen);
+    let mut data = holder.try_borrow_mut_data()?;
+    account.try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+// One production run of a product, its units only join Product::product_stock once inspected
+// and are then sold per batch, so they can not leave the factory after expires_at
+#[account]
+#[derive(InitSpace)]
+pub struct Batch {
+    pub batch_id: u64,
+    pub product_pda: Pubkey,
+    #[max_len(32)]
+    pub batch_number: String,
+    pub quantity: u64,
+    pub raw_material_used: u64,
+    pub produced_at: u64,
+    pub expires_at: u64,
+    pub inspected: bool,
+    pub inspector_pda: Pubkey,
+    pub inspected_at: u64,
+    pub created_at: u64,
+    pub passed: bool,
+    pub remaining: u64, // Released units not yet sold to warehouses
+}
@@ -9,4 +9,5 @@ pub struct CustomerProduct {
     pub owner: Pubkey,
     pub stock_quantity: u64,
     pub purchased_on: u64,
+    pub unit_price: u64, // Mrp paid per unit, refunded if the product is recalled
 }
@@ -16,4 +16,5 @@ pub struct Factory {
     pub contact_info: String,
     pub product_count: u64,
     pub balance: u64,
+    pub frozen: bool, // Set by the platform admin to block payments to and from this account
 }
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+// Partner wallets listed here are never charged platform fees
+#[account]
+#[derive(InitSpace)]
+pub struct FeeExemption {
+    pub account: Pubkey,
+    pub added_by: Pubkey,
+    pub created_at: u64,
+}
+
+impl FeeExemption {
+    pub fn address_for(account: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"fee_exemption", account.as_ref()], &crate::ID).0
+    }
+
+    // Callers pass the payee's exemption PDA unconditionally, it only waives the fee
+    // while the platform owner keeps it open
+    pub fn is_active(info: &AccountInfo) -> bool {
+        info.owner == &crate::ID
+            && info
+                .try_borrow_data()
+                .is_ok_and(|data| data.starts_with(FeeExemption::DISCRIMINATOR))
+    }
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_INSPECTION_SCORE;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub enum InspectionOutcome {
+    Passed,
+    Failed,
+    // Sellable, but only under the conditions listed by the inspector
+    ConditionalPass {
+        #[max_len(120)]
+        conditions: String,
+    },
+}
+
+impl InspectionOutcome {
+    pub fn is_passing(&self) -> bool {
+        !matches!(self, InspectionOutcome::Failed)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            InspectionOutcome::ConditionalPass { conditions } => {
+                !conditions.is_empty() && conditions.len() <= 120
+            }
+            _ => true,
+        }
+    }
+}
+
+// Per criterion scores out of MAX_INSPECTION_SCORE
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, InitSpace)]
+pub struct InspectionScores {
+    pub safety: u8,
+    pub quality: u8,
+    pub packaging: u8,
+    pub labeling: u8,
+}
+
+impl InspectionScores {
+    pub fn is_valid(&self) -> bool {
+        [self.safety, self.quality, self.packaging, self.labeling]
+            .iter()
+            .all(|score| *score <= MAX_INSPECTION_SCORE)
+    }
+}
+
+// Record of a single product inspection at [b"inspection", inspector_profile, inspection_id]
+#[account]
+#[derive(InitSpace)]
+pub struct Inspection {
+    pub inspection_id: u64,
+    pub inspector_pda: Pubkey,
+    pub product_id: u64,
+    pub product_pda: Pubkey,
+    pub outcome: InspectionOutcome,
+    pub scores: InspectionScores,
+    #[max_len(512)]
+    pub notes: String,
+    pub fee_charge_per_product: u64, // Fee schedule of the inspector when the inspection was made
+    pub inspected_at: u64,
+    pub valid_until: u64,
+    pub previous_inspection: Pubkey, // Record this one superseded, default for a first inspection
+}
+
+impl Inspection {
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        self.outcome.is_passing() && now < self.valid_until
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum InspectionDisputeStatus {
+    Open,
+    UnderReview,
+    Upheld,
+    Overturned,
+    Cancelled,
+    Expired,
+}
+
+impl InspectionDisputeStatus {
+    pub fn can_transition_to(self, next: InspectionDisputeStatus) -> bool {
+        use InspectionDisputeStatus::*;
+        matches!(
+            (self, next),
+            (Open, UnderReview)
+                | (Open, Cancelled)
+                | (Open, Expired)
+                | (UnderReview, Upheld)
+                | (UnderReview, Overturned)
+                | (UnderReview, Expired)
+        )
+    }
+}
+
+// One dispute per inspection at [b"inspection_dispute", inspection], the account holds the
+// inspector's frozen payout and the review fee until the second inspector reports back
+#[account]
+#[derive(InitSpace)]
+pub struct InspectionDispute {
+    pub inspection_pda: Pubkey,
+    pub request_pda: Pubkey,
+    pub product_pda: Pubkey,
+    pub inspector_pda: Pubkey,
+    pub raised_by: Pubkey,
+    pub refund_to: Pubkey, // Factory owner that paid the inspection fee
+    #[max_len(256)]
+    pub reason: String,
+    pub frozen_from_balance: u64,
+    pub frozen_from_stake: u64,
+    pub review_fee: u64,
+    pub reviewer_pda: Pubkey,
+    pub review_inspection_pda: Pubkey,
+    pub status: InspectionDisputeStatus,
+    pub created_at: u64,
+    pub review_by: u64, // After this anyone can expire the dispute in the inspector's favour
+    pub resolved_at: u64,
+}
+
+impl InspectionDispute {
+    pub fn frozen(&self) -> u64 {
+        self.frozen_from_balance + self.frozen_from_stake
+    }
+
+    pub fn transition_to(&mut self, next: InspectionDisputeStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(next),
+            SupplyChainErrors::InvalidInspectionDisputeStatus
+        );
+        self.status = next;
+        Ok(())
+    }
+}
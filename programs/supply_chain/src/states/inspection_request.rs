@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum InspectionRequestStatus {
+    Requested,
+    Accepted,
+    Completed,
+    Cancelled,
+}
+
+impl InspectionRequestStatus {
+    pub fn can_transition_to(self, next: InspectionRequestStatus) -> bool {
+        use InspectionRequestStatus::*;
+        matches!(
+            (self, next),
+            (Requested, Accepted) | (Requested, Cancelled) | (Accepted, Completed)
+        )
+    }
+}
+
+// Opened by the factory at [b"inspection_request", product, request_id], the account itself
+// holds the escrowed fee until the chosen inspector submits the inspection
+#[account]
+#[derive(InitSpace)]
+pub struct InspectionRequest {
+    pub request_id: u64,
+    pub product_pda: Pubkey,
+    pub factory_pda: Pubkey,
+    pub requested_by: Pubkey,
+    pub inspector_pda: Pubkey,
+    pub fee: u64,
+    pub released: u64, // Paid out to the inspector after the platform fee, frozen again by a dispute
+    pub status: InspectionRequestStatus,
+    pub inspection_pda: Pubkey, // Default until the inspection is submitted
+    pub created_at: u64,
+    pub accepted_at: u64,
+    pub completed_at: u64,
+}
+
+impl InspectionRequest {
+    pub fn transition_to(&mut self, next: InspectionRequestStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(next),
+            SupplyChainErrors::InvalidInspectionRequestStatus
+        );
+        self.status = next;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+
+// One profile per inspector wallet at [b"inspector_profile", owner], it collects every
+// inspection fee and keeps the fee schedule applied to new inspections
+#[account]
+#[derive(InitSpace)]
+pub struct InspectorProfile {
+    pub owner: Pubkey,
+    #[max_len(32)]
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub fee_charge_per_product: u64,
+    pub balance: u64,
+    pub inspection_count: u64,
+    pub frozen: bool, // Set by the platform admin to block payments to and from this account
+    pub created_at: u64,
+    pub disputes_lost: u64,
+    pub recalls_on_passed: u64, // Recalls of products this inspector had passed
+    pub requests_completed: u64,
+    pub total_turnaround: u64, // Seconds from inspection request to submission, summed
+    pub reputation_score: u16, // Basis points, recomputed whenever a counter changes
+}
+
+impl InspectorProfile {
+    pub fn average_turnaround(&self) -> u64 {
+        self.total_turnaround
+            .checked_div(self.requests_completed)
+            .unwrap_or(0)
+    }
+
+    // Share of inspections that held up, a lost dispute costs two inspections and a recall
+    // of a passed product three. Inspectors without any inspection start at zero
+    pub fn refresh_reputation(&mut self) {
+        let penalty = self
+            .disputes_lost
+            .saturating_mul(2)
+            .saturating_add(self.recalls_on_passed.saturating_mul(3));
+        let score = self
+            .inspection_count
+            .saturating_sub(penalty)
+            .saturating_mul(BPS_DENOMINATOR)
+            .checked_div(self.inspection_count)
+            .unwrap_or(0);
+        self.reputation_score = score as u16;
+    }
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+// Lamports locked by an inspector at [b"inspector_stake", inspector_profile], the stake
+// must cover MIN_INSPECTOR_STAKE to inspect and is slashed into recall funds when a
+// passed product is later recalled
+#[account]
+#[derive(InitSpace)]
+pub struct InspectorStake {
+    pub inspector_pda: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub slashed: u64,
+    pub locked_until: u64, // Stake below the minimum can not be withdrawn before this
+    pub created_at: u64,
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct LogisticsProvider {
+    pub logistic_id: u64,
+    #[max_len(32)]
+    pub name: String,
+    #[max_len(32)]
+    pub transportation_mode: String,
+    #[max_len(126)]
+    pub contact_info: String,
+    pub balance: u64,
+    pub shipment_count: u64,
+    pub active_shipments: u64,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub owner: Pubkey,
+    pub frozen: bool, // Set by the platform admin to block payments to and from this account
+}
@@ -1,12 +1,28 @@
 pub mod user;
 pub mod product;
 pub mod factory;
-pub mod logistics;
+pub mod logistics_provider;
+pub mod shipment;
+pub mod shipment_checkpoint;
 pub mod warehouse;
-pub mod product_inspector;
+pub mod inspector_profile;
+pub mod inspection;
 pub mod transaction;
 pub mod seller;
 pub mod order;
 pub mod seller_product_stock;
 pub mod program_state;
-pub mod customer_product;
\ No newline at end of file
+pub mod customer_product;
+pub mod order_escrow;
+pub mod warehouse_stock;
+pub mod balance_holder;
+pub mod fee_exemption;
+pub mod treasury;
+pub mod batch;
+pub mod price_history;
+pub mod recall_fund;
+pub mod inspection_request;
+pub mod inspector_stake;
+pub mod inspection_dispute;
+pub mod purchase_order;
+pub mod stock_transfer;
@@ -1,5 +1,42 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::SupplyChainErrors;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum OrderStatus {
+    Ordered,
+    Accepted,
+    Rejected,
+    Shipped,
+    Delivered,
+    Cancelled,
+    Disputed,
+    Refunded,
+}
+
+impl OrderStatus {
+    // Every order status change must go through this table
+    pub fn can_transition_to(self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+        matches!(
+            (self, next),
+            (Ordered, Accepted)
+                | (Ordered, Rejected)
+                | (Ordered, Cancelled)
+                | (Ordered, Refunded) // never accepted before the shipment deadline
+                | (Accepted, Shipped)
+                | (Accepted, Cancelled)
+                | (Accepted, Refunded) // never shipped before the shipment deadline
+                | (Shipped, Delivered)
+                | (Shipped, Disputed)
+                | (Rejected, Refunded)
+                | (Cancelled, Refunded)
+                | (Disputed, Delivered) // dispute settled in the warehouse's favour
+                | (Disputed, Refunded)
+        )
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Order{
@@ -15,6 +52,19 @@ pub struct Order{
     pub seller_pda: Pubkey,
     pub logistic_id: u64,
     pub logistic_pda: Pubkey,
-    #[max_len(32)]
-    pub status: String,
-}
\ No newline at end of file
+    pub shipment_pda: Pubkey,
+    pub status: OrderStatus,
+    pub escrow_pda: Pubkey,
+    pub ship_by: u64,
+}
+
+impl Order {
+    pub fn transition_to(&mut self, next: OrderStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(next),
+            SupplyChainErrors::InvalidOrderStatus
+        );
+        self.status = next;
+        Ok(())
+    }
+}
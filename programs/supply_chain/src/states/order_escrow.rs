@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct OrderEscrow {
+    pub order_pda: Pubkey,
+    pub seller_pda: Pubkey,
+    pub warehouse_pda: Pubkey,
+    pub amount: u64,
+    pub payment_mint: Pubkey, // Default for lamport escrows, otherwise held in the escrow vault
+    pub created_at: u64,
+    pub released: bool,
+    pub refunded: bool,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::PRICE_HISTORY_LEN;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PriceEntry {
+    pub product_price: u64,
+    pub mrp: u64,
+    pub changed_at: u64,
+}
+
+// Ring buffer of the latest product_price/mrp values, once full the oldest entry is overwritten
+#[account]
+#[derive(InitSpace)]
+pub struct PriceHistory {
+    pub product_pda: Pubkey,
+    pub entries: [PriceEntry; PRICE_HISTORY_LEN],
+    pub head: u8, // Slot the next entry is written to
+    pub count: u8,
+}
+
+impl PriceHistory {
+    pub fn record(&mut self, product_price: u64, mrp: u64, changed_at: u64) {
+        self.entries[self.head as usize] = PriceEntry {
+            product_price,
+            mrp,
+            changed_at,
+        };
+        self.head = ((self.head as usize + 1) % PRICE_HISTORY_LEN) as u8;
+        if (self.count as usize) < PRICE_HISTORY_LEN {
+            self.count += 1;
+        }
+    }
+}
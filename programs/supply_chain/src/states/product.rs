@@ -20,7 +20,25 @@ pub struct Product {
     pub quality_checked: bool,
     pub inspection_id: u64,
     pub inspector_pda: Pubkey,
+    pub inspection_valid_until: u64, // Sales stop once the current inspection lapses
     pub inspection_fee_paid: bool,
     pub mrp: u64,
+    pub unit_volume: u64, // Warehouse capacity taken by a single unit
     pub created_at: u64,
+    pub batch_count: u64, // Production runs added through restock_product
+    pub recalled: bool,   // Blocks every further sale, holders claim refunds from the RecallFund
+    pub recalled_at: u64,
+    pub inspection_request_count: u64,
+    pub batch_stock: u64, // Part of product_stock released by batches, sold per batch
+}
+
+impl Product {
+    // Units from the initial production run, which never expire
+    pub fn base_stock(&self) -> u64 {
+        self.product_stock.saturating_sub(self.batch_stock)
+    }
+
+    pub fn inspection_valid_at(&self, now: u64) -> bool {
+        self.quality_checked && now < self.inspection_valid_until
+    }
 }
@@ -1,9 +1,64 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::BPS_DENOMINATOR;
+use crate::errors::SupplyChainErrors;
+use crate::states::balance_holder::BalanceHolderKind;
+
+// Platform cut in basis points, taken when a role is paid for goods or services
+// and again when it withdraws its balance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeSchedule {
+    pub withdraw_fee_bps: u16,
+    pub purchase_fee_bps: u16,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct ProgramState {
     pub owner: Pubkey,
-    pub platform_fee: u64,
+    pub pending_owner: Pubkey, // Pubkey::default() when no transfer is in progress
     pub initialized:bool,
+    pub paused: bool, // Halts purchases, orders and withdrawals, refunds stay open
+    pub payment_mint: Pubkey, // SPL mint accepted by the *_with_token instructions
+    pub min_withdraw_amount: u64,
+    pub factory_fees: FeeSchedule,
+    pub warehouse_fees: FeeSchedule,
+    pub seller_fees: FeeSchedule,
+    pub logistics_fees: FeeSchedule,
+    pub inspector_fees: FeeSchedule,
+}
+
+impl ProgramState {
+    pub fn fees_for(&self, kind: BalanceHolderKind) -> FeeSchedule {
+        match kind {
+            BalanceHolderKind::Factory => self.factory_fees,
+            BalanceHolderKind::Warehouse => self.warehouse_fees,
+            BalanceHolderKind::Seller => self.seller_fees,
+            BalanceHolderKind::Logistics => self.logistics_fees,
+            BalanceHolderKind::Inspector => self.inspector_fees,
+        }
+    }
+
+    pub fn fees_for_mut(&mut self, kind: BalanceHolderKind) -> &mut FeeSchedule {
+        match kind {
+            BalanceHolderKind::Factory => &mut self.factory_fees,
+            BalanceHolderKind::Warehouse => &mut self.warehouse_fees,
+            BalanceHolderKind::Seller => &mut self.seller_fees,
+            BalanceHolderKind::Logistics => &mut self.logistics_fees,
+            BalanceHolderKind::Inspector => &mut self.inspector_fees,
+        }
+    }
+
+    pub fn withdraw_fee_for(&self, kind: BalanceHolderKind, amount: u64) -> Result<u64> {
+        bps_of(amount, self.fees_for(kind).withdraw_fee_bps)
+    }
+
+    pub fn purchase_fee_for(&self, kind: BalanceHolderKind, amount: u64) -> Result<u64> {
+        bps_of(amount, self.fees_for(kind).purchase_fee_bps)
+    }
+}
+
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let fee = (amount as u128) * (bps as u128) / (BPS_DENOMINATOR as u128);
+    Ok(u64::try_from(fee).map_err(|_| SupplyChainErrors::Overflow)?)
 }
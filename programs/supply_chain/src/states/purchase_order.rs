@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum PurchaseOrderStatus {
+    Placed,
+    Accepted,
+    Rejected,
+    Cancelled,
+    Shipped,
+    Received,
+    Disputed,
+    Refunded,
+}
+
+impl PurchaseOrderStatus {
+    // Every purchase order status change must go through this table
+    pub fn can_transition_to(self, next: PurchaseOrderStatus) -> bool {
+        use PurchaseOrderStatus::*;
+        matches!(
+            (self, next),
+            (Placed, Accepted)
+                | (Placed, Rejected)
+                | (Placed, Cancelled)
+                | (Accepted, Shipped)
+                | (Accepted, Refunded) // never shipped before the shipment deadline
+                | (Shipped, Received)
+                | (Shipped, Disputed)
+                | (Disputed, Received) // dispute settled in the factory's favour
+                | (Disputed, Refunded)
+        )
+    }
+}
+
+// Warehouse order placed with a factory at [b"purchase_order", warehouse, purchase_order_id].
+// Lamport payments are escrowed in the account itself, token payments in the vault at
+// [b"vault", purchase_order], until the warehouse confirms receipt
+#[account]
+#[derive(InitSpace)]
+pub struct PurchaseOrder {
+    pub purchase_order_id: u64,
+    pub warehouse_pda: Pubkey,
+    pub factory_pda: Pubkey,
+    pub product_pda: Pubkey,
+    pub quantity: u64,
+    pub total_price: u64,
+    pub payment_mint: Pubkey, // Default for lamport payments
+    pub status: PurchaseOrderStatus,
+    pub logistic_pda: Pubkey,
+    pub shipment_pda: Pubkey,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub batch_pda: Pubkey, // Default when the units came from the product's base stock
+    pub ship_by: u64,
+}
+
+impl PurchaseOrder {
+    pub fn transition_to(&mut self, next: PurchaseOrderStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(next),
+            SupplyChainErrors::InvalidPurchaseOrderStatus
+        );
+        self.status = next;
+        Ok(())
+    }
+}
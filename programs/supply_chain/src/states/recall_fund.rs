@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+// Lamports set aside by the factory to refund holders of a recalled product
+#[account]
+#[derive(InitSpace)]
+pub struct RecallFund {
+    pub product_pda: Pubkey,
+    pub recalled_by: Pubkey,
+    pub funded: u64,
+    pub claimed: u64,
+    pub created_at: u64,
+}
@@ -17,4 +17,5 @@ pub struct Seller {
     pub order_count: u64,
     pub balance: u64,
     pub owner: Pubkey,
+    pub frozen: bool, // Set by the platform admin to block payments to and from this account
 }
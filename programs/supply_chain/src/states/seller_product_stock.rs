@@ -11,4 +11,5 @@ pub struct SellerProductStock {
     #[max_len(32)]
     pub stock_price: u64, // Price this seller is offering (can differ from product base price)
     pub created_at: u64,      // When this stock was added
+    pub unit_price: u64,      // Price paid to the warehouse per unit, refunded on recall
 }
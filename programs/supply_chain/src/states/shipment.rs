@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum ShipmentStatus {
+    InTransit,
+    Delivered,
+    Returned, // Order refunded before delivery, no further handoffs
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Shipment {
+    pub shipment_id: u64,
+    pub order_pda: Pubkey, // Seller order or warehouse purchase order being delivered
+    pub provider_pda: Pubkey,
+    pub warehouse_pda: Pubkey,
+    pub seller_pda: Pubkey, // Default for factory to warehouse shipments
+    pub product_id: u64,
+    pub product_pda: Pubkey,
+    pub product_stock: u64,
+    pub shipment_cost: u64,
+    pub status: ShipmentStatus,
+    pub custodian: Pubkey, // Wallet currently holding the goods
+    pub checkpoint_count: u64,
+    pub shipment_started_at: u64,
+    pub shipment_ended_at: u64,
+}
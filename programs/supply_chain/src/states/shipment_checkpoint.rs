@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum CheckpointKind {
+    Checkpoint,
+    Handoff,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ShipmentCheckpoint {
+    pub checkpoint_id: u64,
+    pub shipment_pda: Pubkey,
+    pub kind: CheckpointKind,
+    pub recorded_by: Pubkey,
+    pub from_custodian: Pubkey,
+    pub to_custodian: Pubkey, // Same as from_custodian for plain checkpoints
+    #[max_len(64)]
+    pub location: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    #[max_len(128)]
+    pub condition: String,
+    pub timestamp: u64,
+}
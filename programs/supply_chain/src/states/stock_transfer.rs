@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum StockTransferStatus {
+    InTransit,
+    Completed,
+    Cancelled, // Units went back to the source warehouse before delivery
+}
+
+// Movement of units between two warehouses of the same owner at
+// [b"stock_transfer", from_warehouse, transfer_id]. Transfers without a carrier complete at once
+#[account]
+#[derive(InitSpace)]
+pub struct StockTransfer {
+    pub transfer_id: u64,
+    pub from_warehouse: Pubkey,
+    pub to_warehouse: Pubkey,
+    pub product_pda: Pubkey,
+    pub quantity: u64,
+    pub cost_basis: u64, // Share of the source cost basis carried over to the destination
+    pub logistic_pda: Pubkey, // Default when moved without a carrier
+    pub shipment_pda: Pubkey,
+    pub status: StockTransferStatus,
+    pub created_at: u64,
+    pub completed_at: u64,
+}
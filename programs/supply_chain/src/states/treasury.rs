@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+// Program owned PDA at [b"treasury"] that collects every platform fee, its token
+// fees land in the vault at [b"vault", treasury]
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub lamports_withdrawn: u64,
+    pub tokens_withdrawn: u64,
+    pub created_at: u64,
+}
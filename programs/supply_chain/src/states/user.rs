@@ -1,12 +1,32 @@
 use anchor_lang::prelude::*;
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Factory,
+    Warehouse,
+    Seller,
+    Inspector,
+    Logistics,
+    Customer,
+}
+
+impl Role {
+    pub fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+
+    // Roles that produce or certify goods need the platform owner's approval to be added
+    pub fn is_privileged(self) -> bool {
+        matches!(self, Role::Factory | Role::Inspector)
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct User {
     #[max_len(32)]
     pub name: String,
-    #[max_len(20)]
-    pub role: String,
+    pub roles: u8, // Bitset of `Role::mask` values held by this wallet
     #[max_len(64)]
     pub email: String,
     pub created_at: u64,
@@ -18,6 +38,19 @@ pub struct User {
     pub seller_count: u64,
     pub inspector_count: u64,
     pub product_count: u64,
-    pub is_customer: bool,
     pub is_initialized: bool,
 }
+
+impl User {
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles & role.mask() != 0
+    }
+
+    pub fn add_role(&mut self, role: Role) {
+        self.roles |= role.mask();
+    }
+
+    pub fn remove_role(&mut self, role: Role) {
+        self.roles &= !role.mask();
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::SupplyChainErrors;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Warehouse {
@@ -10,15 +12,36 @@ pub struct Warehouse {
     pub name: String,
     #[max_len(512)]
     pub description: String,
-    pub product_id: u64,
-    pub product_pda: Pubkey,
-    pub product_count: u64,
+    pub product_count: u64, // Total units held across all WarehouseStock accounts
     pub latitude: f64,
     pub longitude: f64,
     pub balance: u64,
     #[max_len(128)]
     pub contact_details: String,
     pub owner: Pubkey,
-    pub warehouse_size: u64,
+    pub warehouse_size: u64, // Capacity, in the same units as Product::unit_volume
+    pub used_capacity: u64,
     pub logistic_count: u64,
+    pub frozen: bool, // Set by the platform admin to block payments to and from this account
+    pub purchase_order_count: u64,
+    pub stock_transfer_count: u64, // Outbound transfers to other warehouses of the same owner
+}
+
+impl Warehouse {
+    pub fn occupy(&mut self, volume: u64) -> Result<()> {
+        let used = self
+            .used_capacity
+            .checked_add(volume)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        require!(
+            used <= self.warehouse_size,
+            SupplyChainErrors::WarehouseCapacityExceeded
+        );
+        self.used_capacity = used;
+        Ok(())
+    }
+
+    pub fn free(&mut self, volume: u64) {
+        self.used_capacity = self.used_capacity.saturating_sub(volume);
+    }
 }
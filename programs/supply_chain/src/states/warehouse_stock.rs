@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::SupplyChainErrors;
+
+#[account]
+#[derive(InitSpace)]
+pub struct WarehouseStock {
+    pub warehouse_id: u64,
+    pub warehouse_pda: Pubkey,
+    pub product_id: u64,
+    pub product_pda: Pubkey,
+    pub quantity: u64,   // Units physically held in the warehouse
+    pub reserved: u64,   // Units promised to open seller orders
+    pub cost_basis: u64, // Total amount paid for the units currently held
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl WarehouseStock {
+    pub fn available(&self) -> u64 {
+        self.quantity.saturating_sub(self.reserved)
+    }
+
+    pub fn receive(&mut self, quantity: u64, cost: u64) -> Result<()> {
+        self.quantity = self
+            .quantity
+            .checked_add(quantity)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        self.cost_basis = self
+            .cost_basis
+            .checked_add(cost)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        Ok(())
+    }
+
+    pub fn reserve(&mut self, quantity: u64) -> Result<()> {
+        require!(
+            self.available() >= quantity,
+            SupplyChainErrors::InsufficientStock
+        );
+        self.reserved = self
+            .reserved
+            .checked_add(quantity)
+            .ok_or(SupplyChainErrors::Overflow)?;
+        Ok(())
+    }
+
+    pub fn release(&mut self, quantity: u64) -> Result<()> {
+        self.reserved = self
+            .reserved
+            .checked_sub(quantity)
+            .ok_or(SupplyChainErrors::InsufficientStock)?;
+        Ok(())
+    }
+
+    // Removes reserved units that leave the warehouse and returns their share of the cost basis
+    pub fn dispatch(&mut self, quantity: u64) -> Result<u64> {
+        self.release(quantity)?;
+        require!(
+            self.quantity >= quantity,
+            SupplyChainErrors::InsufficientStock
+        );
+        let cost = (self.cost_basis as u128)
+            .checked_mul(quantity as u128)
+            .and_then(|v| v.checked_div(self.quantity as u128))
+            .ok_or(SupplyChainErrors::Overflow)? as u64;
+        self.quantity -= quantity;
+        self.cost_basis -= cost;
+        Ok(cost)
+    }
+}